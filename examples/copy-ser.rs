@@ -21,8 +21,8 @@
 // SOFTWARE.
 
 use ser_io::{SerFile, SerWriter};
+use std::error::Error;
 use std::fs::File;
-use std::io::Result;
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -31,14 +31,13 @@ struct Opt {
     output: String,
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<(), Box<dyn Error>> {
     let opt = Opt::from_args();
-    let ser = SerFile::open(&opt.input).unwrap();
+    let ser = SerFile::open(&opt.input)?;
     let mut f = File::create(&opt.output)?;
     let mut w = SerWriter::new(&mut f, &ser.header)?;
-    for i in 0..ser.header.frame_count {
-        let frame = ser.read_frame(i)?;
-        w.write_frame(frame)?;
-    }
-    w.write_timestamps(&ser.timestamps)
+    w.write_frames(ser.frames().collect::<ser_io::Result<Vec<_>>>()?)?;
+    w.write_timestamps(&ser.timestamps)?;
+    w.finish()?;
+    Ok(())
 }
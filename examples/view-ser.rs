@@ -21,7 +21,7 @@
 // SOFTWARE.
 
 use ser_io::SerFile;
-use std::io::Result;
+use std::error::Error;
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -30,9 +30,9 @@ struct Opt {
     filename: String,
 }
 
-fn main() -> Result<()> {
+fn main() -> Result<(), Box<dyn Error>> {
     let opt = Opt::from_args();
-    let ser = SerFile::open(&opt.filename).unwrap();
+    let ser = SerFile::open(&opt.filename)?;
 
     let header = &ser.header;
     println!(
@@ -43,11 +43,11 @@ fn main() -> Result<()> {
     println!("Frame size: {}", header.image_frame_size());
     println!("Pixel depth per plane: {}", header.pixel_depth_per_plane);
     println!("Bytes per pixel: {}", header.bytes_per_pixel());
-    println!("Bayer: {:?}", header.bayer);
-    println!("Endianness: {:?}", header.endianness);
+    println!("Bayer: {}", header.bayer);
+    println!("Endianness: {}", header.endianness);
 
-    for i in 0..header.frame_count {
-        let _bytes = ser.read_frame(i)?;
+    for frame in ser.frames() {
+        let _bytes = frame?;
         // do processing ...
     }
 
@@ -0,0 +1,85 @@
+// MIT License
+//
+// Copyright (c) 2021 Andy Grove
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Nearest-neighbor demosaicing of raw Bayer data
+
+use crate::Bayer;
+
+/// Color of a sample in the color filter array
+#[derive(Clone, Copy)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+/// Colors of the 2x2 color filter array cell, in row-major order
+pub(crate) struct Pattern([Color; 4]);
+
+impl Pattern {
+    /// Pattern for a Bayer encoding, or `None` if it is not a supported RGB filter
+    pub(crate) fn from_bayer(bayer: &Bayer) -> Option<Self> {
+        use Color::*;
+        match bayer {
+            Bayer::RGGB => Some(Pattern([Red, Green, Green, Blue])),
+            Bayer::GRBG => Some(Pattern([Green, Red, Blue, Green])),
+            Bayer::GBRG => Some(Pattern([Green, Blue, Red, Green])),
+            Bayer::BGGR => Some(Pattern([Blue, Green, Green, Red])),
+            _ => None,
+        }
+    }
+}
+
+/// Produce interleaved RGB values for an image of at least 2x2 pixels. Each pixel takes
+/// its colors from the 2x2 cell containing it, averaging the two green samples. For
+/// images with an odd width or height, the last column or row uses the previous cell.
+pub(crate) fn demosaic(pixels: &[u16], width: usize, height: usize, pattern: Pattern) -> Vec<u16> {
+    let cell_origin = |v: usize, len: usize| {
+        let origin = v & !1;
+        if origin + 1 < len {
+            origin
+        } else {
+            origin - 2
+        }
+    };
+
+    let mut rgb = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        let cy = cell_origin(y, height);
+        for x in 0..width {
+            let cx = cell_origin(x, width);
+            let (mut r, mut g, mut b) = (0_u32, 0_u32, 0_u32);
+            for (j, color) in pattern.0.iter().enumerate() {
+                let v = pixels[(cy + j / 2) * width + cx + j % 2] as u32;
+                match color {
+                    Color::Red => r = v,
+                    Color::Green => g += v,
+                    Color::Blue => b = v,
+                }
+            }
+            rgb.push(r as u16);
+            rgb.push((g / 2) as u16);
+            rgb.push(b as u16);
+        }
+    }
+    rgb
+}
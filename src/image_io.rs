@@ -0,0 +1,69 @@
+// MIT License
+//
+// Copyright (c) 2021 Andy Grove
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Conversion of frames to images from the `image` crate
+
+use image::{DynamicImage, ImageBuffer};
+
+use crate::{Bayer, Result, SerError, SerFile};
+
+impl SerFile {
+    /// Convert the frame at the given offset to a `DynamicImage`, for example to save it
+    /// as a PNG. Mono frames become `Luma8` or `Luma16` images depending on the bytes per
+    /// pixel, with pixel data shallower than 8 or 16 bits scaled up to the full range.
+    /// Bayer-encoded frames are demosaiced with `read_frame_rgb`, and interleaved RGB or
+    /// BGR frames are scaled down to 8 bits, to produce an `Rgb8` image. Returns an error
+    /// for color encodings that `read_frame_rgb` does not support.
+    pub fn frame_to_image(&self, i: usize) -> Result<DynamicImage> {
+        let width = self.header.image_width;
+        let height = self.header.image_height;
+        if self.header.is_bayer() {
+            return ImageBuffer::from_raw(width, height, self.read_frame_rgb(i)?)
+                .map(DynamicImage::ImageRgb8)
+                .ok_or(SerError::ImageTooSmall { width, height });
+        }
+        let depth = self.header.pixel_depth_per_plane;
+        let pixels = self.read_frame_u16(i)?;
+        let image = match self.header.bayer {
+            Bayer::Mono if self.header.bytes_per_pixel() == 1 => {
+                let shift = 8_u32.saturating_sub(depth);
+                let pixels = pixels.iter().map(|p| (p << shift) as u8).collect();
+                ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8)
+            }
+            Bayer::Mono => {
+                let shift = 16_u32.saturating_sub(depth);
+                let pixels = pixels.iter().map(|p| p << shift).collect();
+                ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageLuma16)
+            }
+            Bayer::RGB | Bayer::BGR => {
+                let shift = depth.saturating_sub(8);
+                let mut pixels: Vec<u8> = pixels.iter().map(|p| (p >> shift) as u8).collect();
+                if self.header.bayer == Bayer::BGR {
+                    pixels.chunks_exact_mut(3).for_each(|p| p.swap(0, 2));
+                }
+                ImageBuffer::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8)
+            }
+            _ => return Err(SerError::UnsupportedBayer(self.header.bayer.clone())),
+        };
+        image.ok_or(SerError::ImageTooSmall { width, height })
+    }
+}
@@ -22,29 +22,321 @@
 
 #![doc = include_str!("../README.md")]
 
-use std::fs::{self, File};
-use std::io::{Error, ErrorKind, Result, Write};
-use std::str;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
 
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use memmap2::{Mmap, MmapOptions};
+#[cfg(feature = "tokio")]
+mod async_io;
+#[cfg(feature = "crc")]
+mod crc;
+#[cfg(feature = "debayer")]
+mod debayer;
+#[cfg(feature = "image")]
+mod image_io;
 
-const HEADER_SIZE: usize = 178;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeZone, Utc};
+#[cfg(all(feature = "mmap", unix))]
+use memmap2::Advice;
+#[cfg(feature = "mmap")]
+use memmap2::{Mmap, MmapMut, MmapOptions};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-const MAGIC: &str = "LUCAM-RECORDER";
+/// Size of the SER header, in bytes
+pub const HEADER_SIZE: usize = 178;
+
+/// Magic string at the start of a SER file
+pub const MAGIC: &str = "LUCAM-RECORDER";
+
+/// Number of 100-nanosecond ticks between 0001-01-01 and the Unix epoch
+const UNIX_EPOCH_TICKS: u64 = 621_355_968_000_000_000;
+
+/// Result type for reading SER files
+pub type Result<T> = std::result::Result<T, SerError>;
+
+/// Errors that can occur when reading a SER file
+#[derive(Debug)]
+pub enum SerError {
+    /// The header does not start with the expected magic string
+    BadMagic,
+    /// The file is shorter than the header
+    FileTooShort,
+    /// The file does not contain enough bytes for the frames declared in the header
+    TruncatedImageData { expected: usize, actual: usize },
+    /// The requested frame index is out of range
+    InvalidFrameIndex { index: usize, frame_count: usize },
+    /// The image width or height in the header is zero
+    InvalidImageSize { width: u32, height: u32 },
+    /// The pixel depth in the header is outside the valid range of 1 to 16 bits
+    InvalidPixelDepth(u32),
+    /// The operation does not support the pixel depth of the file
+    UnsupportedPixelDepth(u32),
+    /// The frame data cannot be viewed as native `u16` values without copying, because
+    /// the endianness differs from the host or the data is not aligned
+    #[cfg(feature = "bytemuck")]
+    ZeroCopyUnavailable,
+    /// A buffer supplied by the caller does not have the required size
+    BufferSizeMismatch { expected: usize, actual: usize },
+    /// The timestamp trailer does not have the expected length
+    InvalidTrailer { expected: usize, actual: usize },
+    /// The timestamp of the frame at the given offset is earlier than the previous frame
+    TimestampsNotMonotonic(usize),
+    /// The requested region does not fit inside the image
+    InvalidRegion {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// The operation does not support the Bayer encoding of the file
+    UnsupportedBayer(Bayer),
+    /// The image is smaller than the operation requires
+    ImageTooSmall { width: u32, height: u32 },
+    /// The file at the given position has image parameters that differ from the first file
+    IncompatibleFile(usize),
+    /// A new header would change the layout of the image data in the file
+    LayoutMismatch,
+    /// Timestamps were supplied for some frames but not others
+    InconsistentTimestamps,
+    /// The size of the image data declared in the header does not fit in `usize`
+    SizeOverflow,
+    /// The number of frames does not fit in the 32-bit frame count field of the header
+    FrameCountOverflow(usize),
+    /// An encoded header does not have the required length of `HEADER_SIZE` bytes
+    HeaderSizeMismatch { expected: usize, actual: usize },
+    /// A different number of frames was written than the header specifies
+    FrameCountMismatch { expected: usize, written: usize },
+    /// The image width and height are not divisible by the binning factor
+    InvalidBinningFactor {
+        width: u32,
+        height: u32,
+        factor: u32,
+    },
+    /// The checksum written by `SerWriter::write_crc` does not match the image data
+    #[cfg(feature = "crc")]
+    ChecksumMismatch,
+    /// An argument supplied by the caller is not valid for the operation
+    InvalidArgument(&'static str),
+    /// An underlying I/O error
+    Io(io::Error),
+}
+
+impl fmt::Display for SerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerError::BadMagic => write!(f, "bad header"),
+            SerError::FileTooShort => write!(
+                f,
+                "file shorter than header length of {} bytes",
+                HEADER_SIZE
+            ),
+            SerError::TruncatedImageData { expected, actual } => write!(
+                f,
+                "not enough bytes for images: expected {} bytes but file has {} bytes",
+                expected, actual
+            ),
+            SerError::InvalidFrameIndex { index, frame_count } => {
+                write!(f, "frame index {} out of range 0..{}", index, frame_count)
+            }
+            SerError::InvalidImageSize { width, height } => {
+                write!(f, "invalid image size of {} x {} pixels", width, height)
+            }
+            SerError::InvalidPixelDepth(d) => write!(
+                f,
+                "invalid pixel depth of {} bits, expected 1 to 16 bits",
+                d
+            ),
+            SerError::UnsupportedPixelDepth(d) => write!(
+                f,
+                "pixel depth of {} bits is not supported by this operation",
+                d
+            ),
+            #[cfg(feature = "bytemuck")]
+            SerError::ZeroCopyUnavailable => write!(
+                f,
+                "frame data cannot be viewed as native u16 values without copying"
+            ),
+            SerError::BufferSizeMismatch { expected, actual } => write!(
+                f,
+                "buffer has {} bytes but {} bytes are required",
+                actual, expected
+            ),
+            SerError::InvalidTrailer { expected, actual } => write!(
+                f,
+                "trailer has {} bytes but {} bytes are expected",
+                actual, expected
+            ),
+            SerError::TimestampsNotMonotonic(i) => write!(
+                f,
+                "timestamp of frame {} is earlier than the previous frame",
+                i
+            ),
+            SerError::InvalidRegion {
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "region of {} x {} pixels at ({}, {}) does not fit inside the image",
+                width, height, x, y
+            ),
+            SerError::UnsupportedBayer(bayer) => write!(
+                f,
+                "Bayer encoding {:?} is not supported by this operation",
+                bayer
+            ),
+            SerError::ImageTooSmall { width, height } => write!(
+                f,
+                "image size of {}x{} pixels is too small for this operation",
+                width, height
+            ),
+            SerError::IncompatibleFile(i) => write!(
+                f,
+                "file {} has different image parameters from the first file",
+                i
+            ),
+            SerError::LayoutMismatch => write!(
+                f,
+                "header changes the image size, pixel depth, or frame count of the file"
+            ),
+            SerError::InconsistentTimestamps => write!(
+                f,
+                "timestamps must be supplied for either all frames or no frames"
+            ),
+            SerError::SizeOverflow => write!(
+                f,
+                "image data size declared in the header is too large for this platform"
+            ),
+            SerError::FrameCountOverflow(n) => {
+                write!(f, "frame count {} is too large for the header", n)
+            }
+            SerError::HeaderSizeMismatch { expected, actual } => write!(
+                f,
+                "encoded header has {} bytes but {} bytes are required",
+                actual, expected
+            ),
+            SerError::FrameCountMismatch { expected, written } => write!(
+                f,
+                "wrote {} frames when header specifies frame count as {}",
+                written, expected
+            ),
+            SerError::InvalidBinningFactor {
+                width,
+                height,
+                factor,
+            } => write!(
+                f,
+                "image size of {}x{} pixels is not divisible by the binning factor {}",
+                width, height, factor
+            ),
+            #[cfg(feature = "crc")]
+            SerError::ChecksumMismatch => write!(f, "checksum does not match the image data"),
+            SerError::InvalidArgument(msg) => write!(f, "invalid argument: {}", msg),
+            SerError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for SerError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SerError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SerError {
+    fn from(e: io::Error) -> Self {
+        SerError::Io(e)
+    }
+}
+
+impl From<SerError> for io::Error {
+    fn from(e: SerError) -> Self {
+        match e {
+            SerError::Io(e) => e,
+            e => io::Error::new(ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// Bytes backing a SER file
+enum SerData {
+    /// Memory-mapped file
+    #[cfg(feature = "mmap")]
+    Mmap(Mmap),
+    /// Bytes owned in memory
+    Owned(Vec<u8>),
+}
+
+impl Deref for SerData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(feature = "mmap")]
+            SerData::Mmap(mmap) => mmap,
+            SerData::Owned(bytes) => bytes,
+        }
+    }
+}
 
 /// SER file
+///
+/// A file can contain no frames. In that case the frame iterators are empty, methods
+/// that take a frame offset return `SerError::InvalidFrameIndex`, and `duration` and
+/// `average_fps` return `None`.
+///
+/// `SerFile` is `Send` and `Sync`, and reading a frame only borrows the underlying
+/// data, so frames can be read concurrently from multiple threads. For example, with
+/// rayon this is `(0..n).into_par_iter().map(|i| ser.read_frame(i))`, or with scoped
+/// threads from the standard library:
+///
+/// ```no_run
+/// # use ser_io::SerFile;
+/// # fn main() -> ser_io::Result<()> {
+/// let ser = SerFile::open("capture.ser")?;
+/// let n = ser.header.frame_count;
+/// let (first, second) = std::thread::scope(|s| {
+///     let first = s.spawn(|| ser.frames().take(n / 2).count());
+///     let second = s.spawn(|| ser.frames().skip(n / 2).count());
+///     (first.join().unwrap(), second.join().unwrap())
+/// });
+/// assert_eq!(first + second, n);
+/// # Ok(())
+/// # }
+/// ```
 pub struct SerFile {
-    /// Memory-mapped file
-    mmap: Mmap,
+    /// File contents
+    data: SerData,
     /// SER header
     pub header: SerHeader,
-    /// Timestamp in UTC of each frame
+    /// Timestamp in UTC of each frame. This is empty if the file has no trailer, and can
+    /// have fewer entries than there are frames if the trailer is incomplete.
     pub timestamps: Vec<u64>,
+    /// Number of frames declared in the file header. This can be larger than
+    /// `header.frame_count` when a truncated file was opened with `open_lenient`.
+    pub declared_frame_count: usize,
+    /// Number of bytes preceding the per-frame timestamps in the trailer
+    trailer_extra_bytes: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SerHeader {
+    /// Camera series identifier (LU_ID), which is unused by most software
+    pub lu_id: u32,
     /// Image height, in pixels
     pub image_height: u32,
     /// Image width, in pixels
@@ -71,14 +363,132 @@ pub struct SerHeader {
 }
 
 impl SerHeader {
-    /// Total number of image bytes in the file
+    /// Copy of the header with a different frame count, for writing a subset of the
+    /// frames of a file
+    pub fn with_frame_count(&self, frame_count: usize) -> SerHeader {
+        SerHeader {
+            frame_count,
+            ..self.clone()
+        }
+    }
+
+    /// Parse and validate a header from the first `HEADER_SIZE` bytes of `bytes`, which
+    /// can be longer than the header. Returns `SerError::FileTooShort` if there are fewer
+    /// than `HEADER_SIZE` bytes.
+    pub fn parse(bytes: &[u8]) -> Result<SerHeader> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(SerError::FileTooShort);
+        }
+        parse_header(&bytes[0..HEADER_SIZE], false)
+    }
+
+    /// Read just the header of a SER file, without reading or mapping the image data
+    pub fn read_from(filename: &str) -> Result<SerHeader> {
+        let mut bytes = [0_u8; HEADER_SIZE];
+        File::open(filename)?
+            .read_exact(&mut bytes)
+            .map_err(|e| match e.kind() {
+                ErrorKind::UnexpectedEof => SerError::FileTooShort,
+                _ => SerError::Io(e),
+            })?;
+        SerHeader::parse(&bytes)
+    }
+
+    /// Total number of image bytes in the file. Saturates at `usize::MAX` if the size
+    /// does not fit in `usize`, which can only happen for a header that has not been
+    /// validated by opening a file. Use `try_image_data_bytes` for such headers.
     pub fn image_data_bytes(&self) -> usize {
-        self.image_frame_size() * self.frame_count
+        self.checked_image_data_bytes().unwrap_or(usize::MAX)
     }
 
-    /// Number of bytes per image frame
+    /// Number of bytes per image frame. Saturates at `usize::MAX` in the same way as
+    /// `image_data_bytes`.
     pub fn image_frame_size(&self) -> usize {
-        (self.bytes_per_pixel() as u32 * self.image_width * self.image_height) as usize
+        self.checked_image_frame_size().unwrap_or(usize::MAX)
+    }
+
+    /// Total number of image bytes in the file, or `SerError::SizeOverflow` if the size
+    /// does not fit in `usize`
+    pub fn try_image_data_bytes(&self) -> Result<usize> {
+        self.checked_image_data_bytes()
+            .ok_or(SerError::SizeOverflow)
+    }
+
+    /// Number of bytes per image frame, or `SerError::SizeOverflow` if the size does not
+    /// fit in `usize`
+    pub fn try_image_frame_size(&self) -> Result<usize> {
+        self.checked_image_frame_size()
+            .ok_or(SerError::SizeOverflow)
+    }
+
+    /// Number of bytes per image frame, or `None` on overflow
+    fn checked_image_frame_size(&self) -> Option<usize> {
+        (self.num_planes() * self.bytes_per_pixel())
+            .checked_mul(self.image_width as usize)?
+            .checked_mul(self.image_height as usize)
+    }
+
+    /// Total number of image bytes, or `None` on overflow
+    fn checked_image_data_bytes(&self) -> Option<usize> {
+        self.checked_image_frame_size()?
+            .checked_mul(self.frame_count)
+    }
+
+    /// File timestamp in UTC as a `DateTime`, or `None` if it is not set
+    #[cfg(feature = "chrono")]
+    pub fn date_time_utc_as_datetime(&self) -> Option<DateTime<Utc>> {
+        ticks_to_datetime(self.date_time_utc)
+    }
+
+    /// Number of color planes per pixel (3 for RGB/BGR, otherwise 1)
+    pub fn num_planes(&self) -> usize {
+        match self.bayer {
+            Bayer::RGB | Bayer::BGR => 3,
+            _ => 1,
+        }
+    }
+
+    /// Number of color channels (3 for RGB/BGR, otherwise 1 including for raw Bayer data)
+    pub fn channels(&self) -> usize {
+        self.num_planes()
+    }
+
+    /// Category of the image data, derived from the `bayer` field
+    pub fn color_type(&self) -> ColorType {
+        match self.bayer {
+            Bayer::Mono => ColorType::Mono,
+            Bayer::RGB | Bayer::BGR => ColorType::Rgb,
+            Bayer::Unknown(_) => ColorType::Unknown,
+            _ => ColorType::Bayer,
+        }
+    }
+
+    /// Whether the image data is color, either Bayer-encoded or interleaved RGB/BGR
+    pub fn is_color(&self) -> bool {
+        self.is_bayer() || matches!(self.bayer, Bayer::RGB | Bayer::BGR)
+    }
+
+    /// Whether the image data is raw Bayer-encoded color filter array data
+    pub fn is_bayer(&self) -> bool {
+        matches!(
+            self.bayer,
+            Bayer::RGGB
+                | Bayer::GRBG
+                | Bayer::GBRG
+                | Bayer::BGGR
+                | Bayer::CYYM
+                | Bayer::YCMY
+                | Bayer::YMCY
+                | Bayer::MYYC
+        )
+    }
+
+    /// Maximum pixel value for the pixel depth, which can be less than the maximum value
+    /// that fits in the bytes per pixel, for example 4095 for 12-bit data
+    pub fn max_pixel_value(&self) -> u32 {
+        1_u32
+            .checked_shl(self.pixel_depth_per_plane)
+            .map_or(u32::MAX, |v| v - 1)
     }
 
     /// Number of bytes per pixel (either 1 or 2)
@@ -91,224 +501,4320 @@ impl SerHeader {
     }
 }
 
-impl SerFile {
-    /// Open a SER file
-    pub fn open(filename: &str) -> Result<Self> {
-        let file = File::open(&filename)?;
-        let metadata = fs::metadata(&filename)?;
-        let len = metadata.len() as usize;
-        if len < HEADER_SIZE {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "file shorter than header length of 178 bytes",
-            ));
+/// Pixel value statistics for a frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameStats {
+    /// Minimum pixel value
+    pub min: u16,
+    /// Maximum pixel value
+    pub max: u16,
+    /// Mean pixel value
+    pub mean: f64,
+}
+
+/// A frame timestamp in 100 ns ticks since 0001-01-01, as stored in the SER trailer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SerTimestamp(pub u64);
+
+impl SerTimestamp {
+    /// Number of 100 ns ticks since 0001-01-01
+    pub fn ticks(&self) -> u64 {
+        self.0
+    }
+
+    /// Number of nanoseconds since the Unix epoch, which is negative for timestamps
+    /// before 1970
+    pub fn as_unix_nanos(&self) -> i128 {
+        (self.0 as i128 - UNIX_EPOCH_TICKS as i128) * 100
+    }
+
+    /// Timestamp as a `DateTime`, or `None` if it is zero (not set) or out of range for
+    /// `DateTime`
+    #[cfg(feature = "chrono")]
+    pub fn to_datetime(&self) -> Option<DateTime<Utc>> {
+        ticks_to_datetime(self.0)
+    }
+}
+
+impl From<u64> for SerTimestamp {
+    fn from(ticks: u64) -> Self {
+        SerTimestamp(ticks)
+    }
+}
+
+/// A borrowed view of the pixels of a single-plane frame, indexed by coordinates
+#[derive(Debug, Clone)]
+pub struct FrameView<'a> {
+    data: &'a [u8],
+    width: usize,
+    height: usize,
+    bytes_per_pixel: usize,
+    endianness: Endianness,
+}
+
+impl FrameView<'_> {
+    /// Width of the frame in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of the frame in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Value of the pixel at column `x` and row `y`, decoded using the endianness from the
+    /// header. 8-bit pixel data is widened to `u16`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are outside the frame.
+    pub fn get(&self, x: usize, y: usize) -> u16 {
+        assert!(
+            x < self.width && y < self.height,
+            "pixel ({}, {}) out of range for {}x{} frame",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        let offset = (y * self.width + x) * self.bytes_per_pixel;
+        if self.bytes_per_pixel == 1 {
+            return self.data[offset] as u16;
         }
+        let bytes = &self.data[offset..offset + 2];
+        match self.endianness {
+            Endianness::LittleEndian => LittleEndian::read_u16(bytes),
+            Endianness::BigEndian => BigEndian::read_u16(bytes),
+        }
+    }
+}
+
+/// Position of the significant bits within 16-bit pixel values when the pixel depth is
+/// less than 16 bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitAlignment {
+    /// Data is stored in the high bits, with the low bits zero
+    LeftAligned,
+    /// Data is stored in the low bits, with the high bits zero
+    RightAligned,
+    /// Alignment could not be determined
+    Unknown,
+}
 
-        let mmap = unsafe { MmapOptions::new().map(&file)? };
+/// Sizes of the sections of a SER file, in bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    /// Size of the header
+    pub header_size: usize,
+    /// Size of the image data of all frames
+    pub image_data_bytes: usize,
+    /// Size of the data following the image data, which is normally the timestamp trailer
+    pub trailer_bytes: usize,
+    /// Size of the CRC-32 checksum written by `SerWriter::write_crc` at the end of the
+    /// file. This is only detected when the `crc` feature is enabled, and is otherwise
+    /// counted as part of the trailer.
+    pub checksum_bytes: usize,
+    /// Total size of the file
+    pub total_bytes: usize,
+}
 
-        let header_bytes = &mmap[0..HEADER_SIZE];
+/// Builder for constructing a `SerHeader`, for example to pass to `SerWriter::new`.
+/// Defaults to a mono, 8-bit, little-endian header with empty text fields and zero
+/// timestamps.
+pub struct SerHeaderBuilder {
+    header: SerHeader,
+}
 
-        let magic = parse_string(&header_bytes[0..14]);
-        if magic != MAGIC {
-            return Err(Error::new(ErrorKind::InvalidData, "bad header"));
+impl SerHeaderBuilder {
+    pub fn new() -> Self {
+        Self {
+            header: SerHeader {
+                lu_id: 0,
+                image_height: 0,
+                image_width: 0,
+                frame_count: 0,
+                pixel_depth_per_plane: 8,
+                endianness: Endianness::LittleEndian,
+                bayer: Bayer::Mono,
+                observer: String::new(),
+                telescope: String::new(),
+                instrument: String::new(),
+                date_time: 0,
+                date_time_utc: 0,
+            },
         }
+    }
 
-        // unused
-        let _lu_id = parse_u32(&header_bytes[14..18]);
+    /// Set the camera series identifier (LU_ID)
+    pub fn lu_id(mut self, lu_id: u32) -> Self {
+        self.header.lu_id = lu_id;
+        self
+    }
 
-        let bayer = parse_u32(&header_bytes[18..22]);
+    /// Set the image width and height, in pixels
+    pub fn image_size(mut self, width: u32, height: u32) -> Self {
+        self.header.image_width = width;
+        self.header.image_height = height;
+        self
+    }
 
-        let bayer = match bayer {
-            0 => Bayer::Mono,
-            8 => Bayer::RGGB,
-            9 => Bayer::GRBG,
-            10 => Bayer::GBRG,
-            11 => Bayer::BGGR,
-            16 => Bayer::CYYM,
-            17 => Bayer::YCMY,
-            18 => Bayer::YMCY,
-            19 => Bayer::MYYC,
-            100 => Bayer::RGB,
-            101 => Bayer::BGR,
-            _ => Bayer::Unknown(bayer),
-        };
+    /// Set the pixel depth per plane
+    pub fn pixel_depth(mut self, depth: u32) -> Self {
+        self.header.pixel_depth_per_plane = depth;
+        self
+    }
 
-        let endianness = match parse_u32(&header_bytes[22..26]) {
-            0 => Endianness::LittleEndian,
-            _ => Endianness::BigEndian,
-        };
+    /// Set the number of frames
+    pub fn frame_count(mut self, frame_count: usize) -> Self {
+        self.header.frame_count = frame_count;
+        self
+    }
 
-        let image_width = parse_u32(&header_bytes[26..30]);
-        let image_height = parse_u32(&header_bytes[30..34]);
-        let pixel_depth_per_plane = parse_u32(&header_bytes[34..38]);
-        let frame_count = parse_u32(&header_bytes[38..42]) as usize;
-        let observer = parse_string(&header_bytes[42..82]);
-        let instrument = parse_string(&header_bytes[82..122]);
-        let telescope = parse_string(&header_bytes[122..162]);
-        let date_time = parse_u64(&header_bytes[162..170]);
-        let date_time_utc = parse_u64(&header_bytes[170..HEADER_SIZE]);
+    /// Set the endianness of the image data
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.header.endianness = endianness;
+        self
+    }
 
-        let header = SerHeader {
-            image_height,
-            image_width,
-            frame_count,
-            pixel_depth_per_plane,
-            endianness,
-            bayer,
-            observer,
-            telescope,
-            instrument,
-            date_time,
-            date_time_utc,
-        };
+    /// Set the Bayer encoding
+    pub fn bayer(mut self, bayer: Bayer) -> Self {
+        self.header.bayer = bayer;
+        self
+    }
 
-        if len < HEADER_SIZE + header.image_data_bytes() {
-            // TODO could add an option to be able to read valid frames that were
-            // saved in the case of the file being truncated
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "not enough bytes for images",
-            ));
+    /// Set the name of the observer
+    pub fn observer(mut self, observer: &str) -> Self {
+        self.header.observer = observer.to_string();
+        self
+    }
+
+    /// Set the name of the telescope
+    pub fn telescope(mut self, telescope: &str) -> Self {
+        self.header.telescope = telescope.to_string();
+        self
+    }
+
+    /// Set the name of the instrument
+    pub fn instrument(mut self, instrument: &str) -> Self {
+        self.header.instrument = instrument.to_string();
+        self
+    }
+
+    /// Set the file timestamp
+    pub fn date_time(mut self, date_time: u64) -> Self {
+        self.header.date_time = date_time;
+        self
+    }
+
+    /// Set the file timestamp in UTC
+    pub fn date_time_utc(mut self, date_time_utc: u64) -> Self {
+        self.header.date_time_utc = date_time_utc;
+        self
+    }
+
+    /// Build the header
+    pub fn build(self) -> SerHeader {
+        self.header
+    }
+}
+
+impl Default for SerHeaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options for opening a SER file
+pub struct SerOpenOptions {
+    /// Whether to accept truncated files
+    allow_truncated: bool,
+    /// Whether to accept variations of the magic string
+    permissive_magic: bool,
+    /// Whether to read the timestamp trailer
+    read_trailer: bool,
+    /// Whether to memory-map the file rather than reading it into memory
+    memory_map: bool,
+}
+
+impl SerOpenOptions {
+    /// Create options for strictly opening a SER file, the same as `SerFile::open`
+    pub fn new() -> Self {
+        Self {
+            allow_truncated: false,
+            permissive_magic: false,
+            read_trailer: true,
+            memory_map: cfg!(feature = "mmap"),
+        }
+    }
+
+    /// Accept a file that has been truncated, for example because a capture was
+    /// interrupted. Only the whole frames that are present in the file can be read, and
+    /// `header.frame_count` is set accordingly. The frame count declared in the file is
+    /// available as `declared_frame_count`. The timestamp trailer is ignored for a
+    /// truncated file.
+    pub fn allow_truncated(mut self, allow_truncated: bool) -> Self {
+        self.allow_truncated = allow_truncated;
+        self
+    }
+
+    /// Accept any magic string that starts with `LUCAM`, ignoring case, rather than
+    /// requiring exactly `LUCAM-RECORDER`. This allows opening files written by software
+    /// that stores a slightly different magic string or trailing garbage in the field.
+    pub fn permissive_magic(mut self, permissive_magic: bool) -> Self {
+        self.permissive_magic = permissive_magic;
+        self
+    }
+
+    /// Whether to read the timestamp trailer. When disabled, `timestamps` is empty.
+    pub fn read_trailer(mut self, read_trailer: bool) -> Self {
+        self.read_trailer = read_trailer;
+        self
+    }
+
+    /// Whether to memory-map the file rather than reading it into memory. This is enabled
+    /// by default when the `mmap` feature is enabled, and has no effect otherwise.
+    pub fn memory_map(mut self, memory_map: bool) -> Self {
+        self.memory_map = memory_map && cfg!(feature = "mmap");
+        self
+    }
+
+    /// Open a SER file with these options
+    pub fn open(&self, filename: &str) -> Result<SerFile> {
+        SerFile::open_internal(filename, self)
+    }
+}
+
+impl Default for SerOpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerFile {
+    /// Open a SER file. The file is memory-mapped when the `mmap` feature is enabled
+    /// (the default), and otherwise read into memory.
+    pub fn open(filename: &str) -> Result<Self> {
+        SerOpenOptions::new().open(filename)
+    }
+
+    /// Open a SER file that may have been truncated, for example because a capture was
+    /// interrupted. This is shorthand for
+    /// `SerOpenOptions::new().allow_truncated(true).open(filename)`.
+    pub fn open_lenient(filename: &str) -> Result<Self> {
+        SerOpenOptions::new().allow_truncated(true).open(filename)
+    }
+
+    /// Read a SER file from any reader, such as a network stream or an in-memory
+    /// buffer. The entire contents are read into memory rather than memory-mapped.
+    pub fn open_from_reader<R: Read>(mut r: R) -> Result<Self> {
+        let mut bytes = vec![];
+        r.read_to_end(&mut bytes)?;
+        Self::from_bytes(bytes)
+    }
+
+    /// Create a SER file from bytes that are already in memory
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        Self::from_data(SerData::Owned(data), &SerOpenOptions::new())
+    }
+
+    fn open_internal(filename: &str, options: &SerOpenOptions) -> Result<Self> {
+        let file = File::open(filename)?;
+        let metadata = fs::metadata(filename)?;
+        if (metadata.len() as usize) < HEADER_SIZE {
+            return Err(SerError::FileTooShort);
+        }
+
+        #[cfg(feature = "mmap")]
+        if options.memory_map {
+            let mmap = unsafe { MmapOptions::new().map(&file)? };
+            return Self::from_data(SerData::Mmap(mmap), options);
+        }
+
+        let mut bytes = Vec::with_capacity(metadata.len() as usize);
+        (&file).read_to_end(&mut bytes)?;
+        Self::from_data(SerData::Owned(bytes), options)
+    }
+
+    fn from_data(data: SerData, options: &SerOpenOptions) -> Result<Self> {
+        let len = data.len();
+        if len < HEADER_SIZE {
+            return Err(SerError::FileTooShort);
+        }
+
+        let mut header = parse_header(&data[0..HEADER_SIZE], options.permissive_magic)?;
+        let frame_count = header.frame_count;
+
+        let frame_size = header.try_image_frame_size()?;
+        let image_end = header
+            .checked_image_data_bytes()
+            .and_then(|bytes| bytes.checked_add(HEADER_SIZE));
+        match image_end {
+            Some(image_end) if len >= image_end => {}
+            _ if options.allow_truncated => {
+                header.frame_count = (len - HEADER_SIZE) / frame_size;
+            }
+            Some(image_end) => {
+                return Err(SerError::TruncatedImageData {
+                    expected: image_end,
+                    actual: len,
+                })
+            }
+            None => return Err(SerError::SizeOverflow),
         }
 
-        // read optional trailer with timestamp per frame
-        let trailer_offset = HEADER_SIZE + header.image_data_bytes() as usize;
-        let trailer_size = 8_usize * frame_count as usize;
-        let timestamps: Vec<u64> = if len >= trailer_offset + trailer_size {
-            let trailer = &mmap[trailer_offset..trailer_offset + trailer_size];
-            (0..frame_count as usize)
-                .map(|i| parse_u64(&trailer[i..i + 8]))
-                .collect::<Vec<_>>()
+        // read optional trailer with timestamp per frame, which may be incomplete if
+        // writing the file was interrupted. Some recorders write additional 8-byte values
+        // before the per-frame timestamps, in which case the last `frame_count` values
+        // are used.
+        let mut trailer_offset = HEADER_SIZE + header.image_data_bytes();
+        let trailer_len = len - trailer_offset;
+        let mut trailer_extra_bytes = 0;
+        if header.frame_count == frame_count
+            && trailer_len > frame_count.saturating_mul(8)
+            && trailer_len.is_multiple_of(8)
+        {
+            trailer_extra_bytes = trailer_len - 8 * frame_count;
+            trailer_offset += trailer_extra_bytes;
+        }
+        let timestamps: Vec<u64> = if options.read_trailer && header.frame_count == frame_count {
+            let available = (len - trailer_offset) / 8;
+            (0..frame_count.min(available))
+                .map(|i| parse_u64(&data[trailer_offset + i * 8..trailer_offset + i * 8 + 8]))
+                .collect::<Result<Vec<_>>>()?
         } else {
             vec![]
         };
 
         Ok(Self {
-            mmap,
+            data,
             header,
             timestamps,
+            declared_frame_count: frame_count,
+            trailer_extra_bytes,
         })
     }
 
-    /// Read the frame at the given offset
-    pub fn read_frame(&self, i: usize) -> Result<&[u8]> {
-        if i < self.header.frame_count as usize {
-            let offset = HEADER_SIZE + i * self.header.image_frame_size();
-            Ok(&self.mmap[offset..offset + self.header.image_frame_size()])
-        } else {
-            Err(Error::new(ErrorKind::InvalidData, "invalid frame index"))
-        }
+    /// Raw bytes of the observer field in the header, for callers that need to decode
+    /// text that is not UTF-8
+    pub fn observer_bytes(&self) -> &[u8] {
+        &self.data[42..82]
     }
-}
 
-pub struct SerWriter<'a> {
-    header: &'a SerHeader,
-    w: &'a mut dyn Write,
-}
+    /// Raw bytes of the instrument field in the header
+    pub fn instrument_bytes(&self) -> &[u8] {
+        &self.data[82..122]
+    }
 
-impl<'a> SerWriter<'a> {
-    pub fn new(w: &'a mut dyn Write, header: &'a SerHeader) -> Result<Self> {
-        let mut header_bytes: Vec<u8> = Vec::with_capacity(HEADER_SIZE);
-        header_bytes.append(&mut MAGIC.as_bytes().to_vec());
-        header_bytes.write_u32::<LittleEndian>(0)?; // lu_id unused
-        let bayer_n: u32 = match header.bayer {
-            Bayer::Mono => 0,
-            Bayer::RGGB => 8,
-            Bayer::GRBG => 9,
-            Bayer::GBRG => 10,
-            Bayer::BGGR => 11,
-            Bayer::CYYM => 16,
-            Bayer::YCMY => 17,
-            Bayer::YMCY => 18,
-            Bayer::MYYC => 19,
-            Bayer::RGB => 100,
-            Bayer::BGR => 101,
-            Bayer::Unknown(bayer) => bayer,
-        };
-        header_bytes.write_u32::<LittleEndian>(bayer_n)?;
-        header_bytes.write_u32::<LittleEndian>(match header.endianness {
-            Endianness::LittleEndian => 0,
-            Endianness::BigEndian => 1,
-        })?;
-        header_bytes.write_u32::<LittleEndian>(header.image_width)?;
-        header_bytes.write_u32::<LittleEndian>(header.image_height)?;
-        header_bytes.write_u32::<LittleEndian>(header.pixel_depth_per_plane)?;
-        header_bytes.write_u32::<LittleEndian>(header.frame_count as u32)?;
+    /// Raw bytes of the telescope field in the header
+    pub fn telescope_bytes(&self) -> &[u8] {
+        &self.data[122..162]
+    }
 
-        //TODO check length of strings here and pad/truncate as required
-        header_bytes.write_all(header.observer.as_bytes())?;
-        header_bytes.write_all(header.instrument.as_bytes())?;
-        header_bytes.write_all(header.telescope.as_bytes())?;
+    /// Whether the file data is memory-mapped rather than held in memory. Files opened by
+    /// path are memory-mapped unless the `mmap` feature is disabled or memory mapping was
+    /// turned off with `SerOpenOptions::memory_map`.
+    pub fn is_mmapped(&self) -> bool {
+        match self.data {
+            #[cfg(feature = "mmap")]
+            SerData::Mmap(_) => true,
+            SerData::Owned(_) => false,
+        }
+    }
 
-        header_bytes.write_u64::<LittleEndian>(header.date_time)?;
-        header_bytes.write_u64::<LittleEndian>(header.date_time_utc)?;
+    /// Advise the operating system that the file will be read sequentially from start to
+    /// end, so that it can read ahead more aggressively. This only has an effect for a
+    /// memory-mapped file on Unix platforms, and does nothing otherwise.
+    pub fn advise_sequential(&self) -> Result<()> {
+        #[cfg(all(feature = "mmap", unix))]
+        if let SerData::Mmap(mmap) = &self.data {
+            mmap.advise(Advice::Sequential)?;
+        }
+        Ok(())
+    }
 
-        assert!(header_bytes.len() == HEADER_SIZE);
+    /// Number of bytes in the trailer before the per-frame timestamps, which is non-zero
+    /// when the recorder wrote additional values into the trailer. These bytes are
+    /// skipped when reading the timestamps.
+    pub fn trailer_extra_bytes(&self) -> usize {
+        self.trailer_extra_bytes
+    }
 
-        w.write_all(&header_bytes)?;
+    /// Sizes of the header, image data, and trailer of the file. Returns an error if the
+    /// file is too short for the frames declared in the header.
+    pub fn layout(&self) -> Result<Layout> {
+        let image_data_bytes = self.header.image_data_bytes();
+        let checksum_bytes = self.checksum_bytes();
+        let trailer_bytes = self
+            .data
+            .len()
+            .checked_sub(HEADER_SIZE + image_data_bytes + checksum_bytes)
+            .ok_or(SerError::TruncatedImageData {
+                expected: HEADER_SIZE + image_data_bytes,
+                actual: self.data.len(),
+            })?;
+        Ok(Layout {
+            header_size: HEADER_SIZE,
+            image_data_bytes,
+            trailer_bytes,
+            checksum_bytes,
+            total_bytes: self.data.len(),
+        })
+    }
 
-        Ok(Self { header, w })
+    /// Number of whole frames that fit in the file based on its length, regardless of the
+    /// frame count declared in the header. Any trailer bytes are counted as image data,
+    /// so this can exceed the declared frame count for a file with timestamps.
+    pub fn frames_on_disk(&self) -> usize {
+        self.data.len().saturating_sub(HEADER_SIZE) / self.header.image_frame_size()
     }
 
-    pub fn write_frame(&mut self, frame: &[u8]) -> Result<()> {
-        if self.header.image_frame_size() == frame.len() {
-            self.w.write_all(frame)
-        } else {
-            Err(Error::new(
-                ErrorKind::InvalidData,
-                format!(
-                    "Cannot write image with {} bytes when header specifies image size as {} bytes",
-                    frame.len(),
-                    self.header.image_frame_size()
-                ),
-            ))
+    /// Check the CRC-32 checksum of the image data written by `SerWriter::write_crc`.
+    /// Returns `None` if the file does not end with a checksum.
+    #[cfg(feature = "crc")]
+    pub fn check_crc(&self) -> Option<bool> {
+        if self.checksum_bytes() == 0 {
+            return None;
         }
+        Some(self.image_data_crc().finish() == parse_u32(&self.data[self.data.len() - 4..]).ok()?)
     }
 
-    pub fn write_timestamps(&mut self, timestamps: &[u64]) -> Result<()> {
-        let mut header_bytes = Vec::with_capacity(4 * timestamps.len());
-        for ts in timestamps {
-            header_bytes.write_u64::<LittleEndian>(*ts)?;
+    /// Checksum of the image data, which can be extended with further frames
+    #[cfg(feature = "crc")]
+    fn image_data_crc(&self) -> crc::Crc32 {
+        let mut crc = crc::Crc32::new();
+        crc.update(&self.data[HEADER_SIZE..HEADER_SIZE + self.header.image_data_bytes()]);
+        crc
+    }
+
+    /// Number of bytes at the end of the file taken by a checksum written by
+    /// `SerWriter::write_crc`, which follows the image data or the timestamp trailer
+    fn checksum_bytes(&self) -> usize {
+        let extra = match self
+            .data
+            .len()
+            .checked_sub(HEADER_SIZE + self.header.image_data_bytes())
+        {
+            Some(extra) if cfg!(feature = "crc") && extra >= 4 => extra,
+            _ => return 0,
+        };
+        let trailer = extra - 4;
+        if trailer == 0 || trailer == self.header.frame_count.saturating_mul(8) {
+            4
+        } else {
+            0
         }
-        self.w.write_all(&header_bytes)
     }
-}
 
-#[derive(Debug)]
-pub enum Bayer {
-    Mono,
-    RGGB,
-    GRBG,
-    GBRG,
-    BGGR,
-    CYYM,
-    YCMY,
-    YMCY,
-    MYYC,
-    RGB,
-    BGR,
-    Unknown(u32),
-}
+    /// Check the internal consistency of the file, returning an error describing the
+    /// first problem found. This checks the magic string, that the file contains all of
+    /// the frames declared in the header, that the timestamp trailer (if present) has a
+    /// timestamp for each frame, and that the timestamps never decrease. With the `crc`
+    /// feature, this also checks the checksum written by `SerWriter::write_crc`, if any.
+    pub fn verify(&self) -> Result<()> {
+        if parse_string(&self.data[0..14]) != MAGIC {
+            return Err(SerError::BadMagic);
+        }
 
-#[derive(Debug)]
-pub enum Endianness {
-    LittleEndian,
-    BigEndian,
-}
+        let image_end = self
+            .header
+            .image_frame_size()
+            .checked_mul(self.declared_frame_count)
+            .and_then(|bytes| bytes.checked_add(HEADER_SIZE))
+            .ok_or(SerError::SizeOverflow)?;
+        if self.data.len() < image_end {
+            return Err(SerError::TruncatedImageData {
+                expected: image_end,
+                actual: self.data.len(),
+            });
+        }
 
-/// Parse a little-endian u32
-fn parse_u32(buf: &[u8]) -> u32 {
-    let mut buf = buf;
-    buf.read_u32::<LittleEndian>().unwrap()
-}
+        let trailer_size =
+            self.data.len() - image_end - self.trailer_extra_bytes - self.checksum_bytes();
+        let expected_trailer_size = self.declared_frame_count.saturating_mul(8);
+        if trailer_size > 0 && trailer_size != expected_trailer_size {
+            return Err(SerError::InvalidTrailer {
+                expected: expected_trailer_size,
+                actual: trailer_size,
+            });
+        }
 
-/// Parse a little-endian u64
-fn parse_u64(buf: &[u8]) -> u64 {
-    let mut buf = buf;
-    buf.read_u64::<LittleEndian>().unwrap()
-}
+        if let Some(i) =
+            (1..self.timestamps.len()).find(|&i| self.timestamps[i] < self.timestamps[i - 1])
+        {
+            return Err(SerError::TimestampsNotMonotonic(i));
+        }
+
+        #[cfg(feature = "crc")]
+        if self.check_crc() == Some(false) {
+            return Err(SerError::ChecksumMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Read the frame at the given offset
+    pub fn read_frame(&self, i: usize) -> Result<&[u8]> {
+        if i >= self.header.frame_count {
+            return Err(SerError::InvalidFrameIndex {
+                index: i,
+                frame_count: self.header.frame_count,
+            });
+        }
+        self.frame_bytes(i, i + 1)
+    }
+
+    /// Read the contiguous bytes of the frames from `start` up to but not including `end`
+    pub fn read_frames(&self, start: usize, end: usize) -> Result<&[u8]> {
+        if end > self.header.frame_count || start > end {
+            return Err(SerError::InvalidFrameIndex {
+                index: if start > end { start } else { end },
+                frame_count: self.header.frame_count,
+            });
+        }
+        self.frame_bytes(start, end)
+    }
+
+    /// Bytes of the frames from `start` up to but not including `end`, checked against the
+    /// length of the file data. The header fields are public, so they may have been changed
+    /// to declare more or larger frames than the file contains since it was opened.
+    fn frame_bytes(&self, start: usize, end: usize) -> Result<&[u8]> {
+        let frame_size = self.header.image_frame_size();
+        let offset = |i: usize| {
+            i.checked_mul(frame_size)
+                .and_then(|bytes| bytes.checked_add(HEADER_SIZE))
+                .ok_or(SerError::SizeOverflow)
+        };
+        let (start, end) = (offset(start)?, offset(end)?);
+        self.data
+            .get(start..end)
+            .ok_or(SerError::TruncatedImageData {
+                expected: end,
+                actual: self.data.len(),
+            })
+    }
+
+    /// Byte offset of the frame at the given offset from the start of the file, or
+    /// `None` if the frame index is out of range
+    pub fn frame_offset(&self, i: usize) -> Option<usize> {
+        if i < self.header.frame_count {
+            i.checked_mul(self.header.image_frame_size())?
+                .checked_add(HEADER_SIZE)
+        } else {
+            None
+        }
+    }
+
+    /// Alignment in bytes of the start of the frame at the given offset in memory, which is
+    /// the largest power of two that divides its address. The header is 178 bytes long,
+    /// so even in a memory-mapped file the first frame is aligned to 2 bytes but not 4,
+    /// and later frames depend on the frame size. This can be used to decide whether the
+    /// frame can be processed with aligned loads or must be copied.
+    pub fn frame_alignment(&self, i: usize) -> Result<usize> {
+        let address = self.read_frame(i)?.as_ptr() as usize;
+        Ok(1 << address.trailing_zeros())
+    }
+
+    /// Copy the frame at the given offset into a caller-supplied buffer, which must be
+    /// exactly `image_frame_size()` bytes
+    pub fn read_frame_into(&self, i: usize, buf: &mut [u8]) -> Result<()> {
+        let frame = self.read_frame(i)?;
+        if buf.len() != frame.len() {
+            return Err(SerError::BufferSizeMismatch {
+                expected: frame.len(),
+                actual: buf.len(),
+            });
+        }
+        buf.copy_from_slice(frame);
+        Ok(())
+    }
+
+    /// Write the raw bytes of the frame at the given offset to a writer
+    pub fn write_frame_to(&self, i: usize, w: &mut dyn Write) -> Result<()> {
+        w.write_all(self.read_frame(i)?)?;
+        Ok(())
+    }
+
+    /// Write the raw bytes of the frame at the given offset to a new file
+    pub fn write_frame_to_file(&self, i: usize, filename: &str) -> Result<()> {
+        let mut file = File::create(filename)?;
+        self.write_frame_to(i, &mut file)
+    }
+
+    /// Read a `width` by `height` region of interest with its top left corner at
+    /// (`x`, `y`) from the frame at the given offset. The returned bytes use the same
+    /// pixel encoding as `read_frame`.
+    pub fn read_frame_roi(
+        &self,
+        i: usize,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>> {
+        let fits =
+            |start: u32, len: u32, max: u32| start.checked_add(len).is_some_and(|end| end <= max);
+        if !fits(x, width, self.header.image_width) || !fits(y, height, self.header.image_height) {
+            return Err(SerError::InvalidRegion {
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+        let frame = self.read_frame(i)?;
+        let pixel_size = self.header.num_planes() * self.header.bytes_per_pixel();
+        let stride = self.header.image_width as usize * pixel_size;
+        let row_len = width as usize * pixel_size;
+        let mut roi = Vec::with_capacity(row_len * height as usize);
+        for row in y as usize..(y + height) as usize {
+            let start = row * stride + x as usize * pixel_size;
+            roi.extend_from_slice(&frame[start..start + row_len]);
+        }
+        Ok(roi)
+    }
+
+    /// Read the frame at the given offset and decode each pixel as a `u16`, using the
+    /// endianness from the header. 8-bit pixel data is widened to `u16`.
+    pub fn read_frame_u16(&self, i: usize) -> Result<Vec<u16>> {
+        let frame = self.read_frame(i)?;
+        if self.header.bytes_per_pixel() == 1 {
+            return Ok(frame.iter().map(|b| *b as u16).collect());
+        }
+        let mut pixels = vec![0_u16; frame.len() / 2];
+        let mut buf = frame;
+        match self.header.endianness {
+            Endianness::LittleEndian => buf.read_u16_into::<LittleEndian>(&mut pixels)?,
+            Endianness::BigEndian => buf.read_u16_into::<BigEndian>(&mut pixels)?,
+        }
+        Ok(pixels)
+    }
+
+    /// View the pixels of the frame at the given offset by coordinates, without copying
+    /// the frame. Returns an error for RGB and BGR files, which have more than one plane.
+    pub fn frame_view(&self, i: usize) -> Result<FrameView<'_>> {
+        if self.header.num_planes() != 1 {
+            return Err(SerError::UnsupportedBayer(self.header.bayer.clone()));
+        }
+        Ok(FrameView {
+            data: self.read_frame(i)?,
+            width: self.header.image_width as usize,
+            height: self.header.image_height as usize,
+            bytes_per_pixel: self.header.bytes_per_pixel(),
+            endianness: self.header.endianness.clone(),
+        })
+    }
+
+    /// Read a frame of 16-bit pixel data as `u16` values in the host's native byte order,
+    /// swapping bytes only when the endianness of the file differs from the host. This
+    /// allocates a new buffer on each call. Returns an error for 8-bit pixel data.
+    pub fn read_frame_native(&self, i: usize) -> Result<Vec<u16>> {
+        if self.header.bytes_per_pixel() != 2 {
+            return Err(SerError::UnsupportedPixelDepth(
+                self.header.pixel_depth_per_plane,
+            ));
+        }
+        let frame = self.read_frame(i)?;
+        let mut pixels: Vec<u16> = frame
+            .chunks_exact(2)
+            .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+            .collect();
+        let file_is_little_endian = matches!(self.header.endianness, Endianness::LittleEndian);
+        if file_is_little_endian != cfg!(target_endian = "little") {
+            pixels.iter_mut().for_each(|p| *p = p.swap_bytes());
+        }
+        Ok(pixels)
+    }
+
+    /// Timestamp in UTC of the frame at the given offset, or `None` if the file has no
+    /// timestamp for this frame
+    pub fn frame_timestamp(&self, i: usize) -> Option<u64> {
+        self.timestamps.get(i).copied()
+    }
+
+    /// Offsets of the frames sorted by timestamp, for files where frames were not written
+    /// in chronological order. Frames with equal timestamps keep their order in the file.
+    /// Returns `None` if the file does not have a timestamp for every frame.
+    pub fn chronological_order(&self) -> Option<Vec<usize>> {
+        if !self.has_timestamps() {
+            return None;
+        }
+        let mut indices: Vec<usize> = (0..self.header.frame_count).collect();
+        indices.sort_by_key(|&i| self.timestamps[i]);
+        Some(indices)
+    }
+
+    /// Write the frame timestamps as CSV with a header row and columns for the frame
+    /// offset and the raw ticks. With the `chrono` feature, a third column has the time in
+    /// UTC in RFC 3339 format, which is left empty for a zero timestamp. Only the header
+    /// row is written if the file has no timestamps.
+    pub fn write_timestamps_csv(&self, w: &mut dyn Write) -> Result<()> {
+        #[cfg(feature = "chrono")]
+        {
+            use chrono::SecondsFormat;
+
+            writeln!(w, "frame,ticks,utc_iso")?;
+            for (i, ts) in self.timestamps.iter().enumerate() {
+                let iso = ticks_to_datetime(*ts)
+                    .map(|dt| dt.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+                    .unwrap_or_default();
+                writeln!(w, "{},{},{}", i, ts, iso)?;
+            }
+        }
+        #[cfg(not(feature = "chrono"))]
+        {
+            writeln!(w, "frame,ticks")?;
+            for (i, ts) in self.timestamps.iter().enumerate() {
+                writeln!(w, "{},{}", i, ts)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterate over the frame timestamps as `SerTimestamp` values
+    pub fn typed_timestamps(&self) -> impl ExactSizeIterator<Item = SerTimestamp> + '_ {
+        self.timestamps.iter().map(|&ts| SerTimestamp(ts))
+    }
+
+    /// Whether the file contains the optional trailer with a timestamp for every frame
+    pub fn has_timestamps(&self) -> bool {
+        !self.timestamps.is_empty() && self.timestamps.len() == self.header.frame_count
+    }
+
+    /// Whether the first and last frame timestamps fall between the years 2000 and 2100,
+    /// as a sanity check that the trailer really contains timestamps. Returns `false` if
+    /// there are no timestamps.
+    pub fn timestamps_look_valid(&self) -> bool {
+        // 2000-01-01 and 2100-01-01 in 100 ns ticks since 0001-01-01
+        const MIN_TICKS: u64 = 630_822_816_000_000_000;
+        const MAX_TICKS: u64 = 662_380_416_000_000_000;
+        match (self.timestamps.first(), self.timestamps.last()) {
+            (Some(first), Some(last)) => {
+                (MIN_TICKS..MAX_TICKS).contains(first) && (MIN_TICKS..MAX_TICKS).contains(last)
+            }
+            _ => false,
+        }
+    }
+
+    /// Time between the first and last frame timestamps, or `None` if there are fewer
+    /// than two timestamps
+    pub fn duration(&self) -> Option<Duration> {
+        if self.timestamps.len() < 2 {
+            return None;
+        }
+        let first = self.timestamps[0];
+        let last = self.timestamps[self.timestamps.len() - 1];
+        last.checked_sub(first).map(ticks_to_duration)
+    }
+
+    /// Find gaps in the frame timestamps that suggest frames were dropped during capture,
+    /// where the interval between consecutive frames exceeds `tolerance` times the median
+    /// interval. Returns the offset of the frame following each gap and the size of the
+    /// gap in 100 ns ticks. Returns no gaps if there are fewer than two timestamps.
+    pub fn detect_dropped_frames(&self, tolerance: f64) -> Vec<(usize, u64)> {
+        let intervals: Vec<u64> = self
+            .timestamps
+            .windows(2)
+            .map(|w| w[1].saturating_sub(w[0]))
+            .collect();
+        if intervals.is_empty() {
+            return vec![];
+        }
+        let mut sorted = intervals.clone();
+        sorted.sort_unstable();
+        let threshold = tolerance * sorted[sorted.len() / 2] as f64;
+        intervals
+            .iter()
+            .enumerate()
+            .filter(|(_, &gap)| gap as f64 > threshold)
+            .map(|(i, &gap)| (i + 1, gap))
+            .collect()
+    }
+
+    /// Average number of frames per second based on the frame timestamps, or `None` if
+    /// there are fewer than two timestamps or they span no time
+    pub fn average_fps(&self) -> Option<f64> {
+        let secs = self.duration()?.as_secs_f64();
+        if secs > 0.0 {
+            Some((self.timestamps.len() - 1) as f64 / secs)
+        } else {
+            None
+        }
+    }
+
+    /// Compute the minimum, maximum, and mean pixel values of the frame at the given offset
+    pub fn frame_stats(&self, i: usize) -> Result<FrameStats> {
+        let pixels = self.read_frame_u16(i)?;
+        if pixels.is_empty() {
+            return Ok(FrameStats {
+                min: 0,
+                max: 0,
+                mean: 0.0,
+            });
+        }
+        let (min, max, sum) = pixels
+            .iter()
+            .fold((u16::MAX, u16::MIN, 0_u64), |(min, max, sum), p| {
+                (min.min(*p), max.max(*p), sum + *p as u64)
+            });
+        Ok(FrameStats {
+            min,
+            max,
+            mean: sum as f64 / pixels.len() as f64,
+        })
+    }
+
+    /// Count the pixel values of the frame at the given offset in `bins` equally sized
+    /// bins spanning the full value range (0 to 255 for 8-bit data or 0 to 65535 for
+    /// 16-bit data). Returns an empty histogram if `bins` is zero.
+    pub fn frame_histogram(&self, i: usize, bins: usize) -> Result<Vec<u64>> {
+        let pixels = self.read_frame_u16(i)?;
+        let mut histogram = vec![0_u64; bins];
+        if bins == 0 {
+            return Ok(histogram);
+        }
+        let range = 1_usize << (8 * self.header.bytes_per_pixel());
+        for p in pixels {
+            histogram[p as usize * bins / range] += 1;
+        }
+        Ok(histogram)
+    }
+
+    /// View the frame at the given offset as `u16` pixel values without copying. This
+    /// succeeds only when the pixel data is 16-bit, the endianness of the file matches
+    /// the host, and the frame data is aligned to 2 bytes (which is always the case for
+    /// memory-mapped files). Otherwise an error is returned and `read_frame_native` can
+    /// be used instead.
+    #[cfg(feature = "bytemuck")]
+    pub fn read_frame_u16_zerocopy(&self, i: usize) -> Result<&[u16]> {
+        if self.header.bytes_per_pixel() != 2 {
+            return Err(SerError::UnsupportedPixelDepth(
+                self.header.pixel_depth_per_plane,
+            ));
+        }
+        let file_is_little_endian = matches!(self.header.endianness, Endianness::LittleEndian);
+        if file_is_little_endian != cfg!(target_endian = "little") {
+            return Err(SerError::ZeroCopyUnavailable);
+        }
+        bytemuck::try_cast_slice(self.read_frame(i)?).map_err(|_| SerError::ZeroCopyUnavailable)
+    }
+
+    /// Split the interleaved pixels of the frame at the given offset of an RGB or BGR file
+    /// into separate red, green, and blue planes, in that order. Each plane keeps the
+    /// byte encoding of the file, so it has the same number of bytes per pixel.
+    pub fn read_frame_planes(&self, i: usize) -> Result<[Vec<u8>; 3]> {
+        let order = match self.header.bayer {
+            Bayer::RGB => [0, 1, 2],
+            Bayer::BGR => [2, 1, 0],
+            _ => return Err(SerError::UnsupportedBayer(self.header.bayer.clone())),
+        };
+        let bpp = self.header.bytes_per_pixel();
+        let frame = self.read_frame(i)?;
+        let mut planes = [
+            Vec::with_capacity(frame.len() / 3),
+            Vec::with_capacity(frame.len() / 3),
+            Vec::with_capacity(frame.len() / 3),
+        ];
+        for pixel in frame.chunks_exact(3 * bpp) {
+            for (plane, &channel) in planes.iter_mut().zip(order.iter()) {
+                plane.extend_from_slice(&pixel[channel * bpp..(channel + 1) * bpp]);
+            }
+        }
+        Ok(planes)
+    }
+
+    /// Demosaic the raw Bayer data of the frame at the given offset into interleaved 8-bit
+    /// RGB, using the nearest samples of each color within the 2x2 filter cell. Pixel
+    /// data deeper than 8 bits is scaled down to 8 bits. Only the RGGB, GRBG, GBRG, and
+    /// BGGR patterns are supported, and the image must be at least 2x2 pixels.
+    #[cfg(feature = "debayer")]
+    pub fn read_frame_rgb(&self, i: usize) -> Result<Vec<u8>> {
+        let pattern = debayer::Pattern::from_bayer(&self.header.bayer)
+            .ok_or_else(|| SerError::UnsupportedBayer(self.header.bayer.clone()))?;
+        let width = self.header.image_width as usize;
+        let height = self.header.image_height as usize;
+        if width < 2 || height < 2 {
+            return Err(SerError::ImageTooSmall {
+                width: self.header.image_width,
+                height: self.header.image_height,
+            });
+        }
+        let pixels = self.read_frame_u16(i)?;
+        let shift = self.header.pixel_depth_per_plane.saturating_sub(8);
+        Ok(debayer::demosaic(&pixels, width, height, pattern)
+            .iter()
+            .map(|v| (v >> shift) as u8)
+            .collect())
+    }
+
+    /// Average the frames at the given offsets pixel by pixel, returning the mean value of
+    /// each pixel rounded down
+    pub fn average_frames(&self, indices: &[usize]) -> Result<Vec<u16>> {
+        if indices.is_empty() {
+            return Err(SerError::InvalidArgument("no frames to average"));
+        }
+        let mut sums = vec![0_u64; self.header.image_frame_size() / self.header.bytes_per_pixel()];
+        for &i in indices {
+            for (sum, p) in sums.iter_mut().zip(self.read_frame_u16(i)?) {
+                *sum += p as u64;
+            }
+        }
+        let n = indices.len() as u64;
+        Ok(sums.iter().map(|sum| (sum / n) as u16).collect())
+    }
+
+    /// Compute the median of the frames at the given offsets pixel by pixel, which is less
+    /// sensitive to outliers than `average_frames` when creating master dark and flat
+    /// frames. For an even number of frames, the mean of the two middle values is used,
+    /// rounded down. The decoded pixels of all of the selected frames are held in memory
+    /// at once, which takes two bytes per pixel per frame.
+    pub fn median_frame(&self, indices: &[usize]) -> Result<Vec<u16>> {
+        if indices.is_empty() {
+            return Err(SerError::InvalidArgument("no frames to compute median"));
+        }
+        let frames = indices
+            .iter()
+            .map(|&i| self.read_frame_u16(i))
+            .collect::<Result<Vec<_>>>()?;
+        let n = frames.len();
+        let mut values = vec![0_u16; n];
+        Ok((0..frames[0].len())
+            .map(|p| {
+                for (value, frame) in values.iter_mut().zip(&frames) {
+                    *value = frame[p];
+                }
+                let (_, &mut upper, _) = values.select_nth_unstable(n / 2);
+                if n % 2 == 1 {
+                    upper
+                } else {
+                    let lower = *values[..n / 2].iter().max().unwrap();
+                    ((lower as u32 + upper as u32) / 2) as u16
+                }
+            })
+            .collect())
+    }
+
+    /// Bin the frame at the given offset by averaging each `factor` x `factor` block of
+    /// pixels, returning the binned width, height, and pixel values. Each color plane is
+    /// binned separately. The image width and height must be divisible by `factor`.
+    pub fn read_frame_binned(&self, i: usize, factor: u32) -> Result<(u32, u32, Vec<u16>)> {
+        let (width, height) = (self.header.image_width, self.header.image_height);
+        if factor == 0 || width % factor != 0 || height % factor != 0 {
+            return Err(SerError::InvalidBinningFactor {
+                width,
+                height,
+                factor,
+            });
+        }
+        let pixels = self.read_frame_u16(i)?;
+        let planes = self.header.num_planes();
+        let (factor, width) = (factor as usize, width as usize);
+        let (binned_width, binned_height) = (width / factor, height as usize / factor);
+        let mut sums = vec![0_u64; binned_width * binned_height * planes];
+        for (j, p) in pixels.iter().enumerate() {
+            let (pixel, plane) = (j / planes, j % planes);
+            let (x, y) = (pixel % width / factor, pixel / width / factor);
+            sums[(y * binned_width + x) * planes + plane] += *p as u64;
+        }
+        let n = (factor * factor) as u64;
+        Ok((
+            binned_width as u32,
+            binned_height as u32,
+            sums.iter().map(|sum| (sum / n) as u16).collect(),
+        ))
+    }
+
+    /// Whether frame `i` of this file and frame `j` of `other` hold the same pixel values
+    /// after decoding, regardless of the endianness of each file. Frames with different
+    /// image sizes or numbers of planes are never equal.
+    pub fn frames_equal_logical(&self, i: usize, other: &SerFile, j: usize) -> Result<bool> {
+        if self.header.image_width != other.header.image_width
+            || self.header.image_height != other.header.image_height
+            || self.header.num_planes() != other.header.num_planes()
+        {
+            return Ok(false);
+        }
+        Ok(self.read_frame_u16(i)? == other.read_frame_u16(j)?)
+    }
+
+    /// Subtract frame `b` from frame `a` pixel by pixel
+    pub fn frame_diff(&self, a: usize, b: usize) -> Result<Vec<i32>> {
+        let a = self.read_frame_u16(a)?;
+        let b = self.read_frame_u16(b)?;
+        Ok(a.iter()
+            .zip(b.iter())
+            .map(|(a, b)| *a as i32 - *b as i32)
+            .collect())
+    }
+
+    /// Score the sharpness of the frame at the given offset as the variance of the
+    /// Laplacian, for ranking frames by quality. Pixel values are scaled by the pixel
+    /// depth so that scores are comparable between files, and color planes are averaged.
+    /// Higher scores indicate sharper frames.
+    pub fn frame_sharpness(&self, i: usize) -> Result<f64> {
+        let pixels = self.read_frame_u16(i)?;
+        let planes = self.header.num_planes();
+        let scale = self.header.max_pixel_value() as f64 * planes as f64;
+        let luma: Vec<f64> = pixels
+            .chunks_exact(planes)
+            .map(|p| p.iter().map(|v| *v as f64).sum::<f64>() / scale)
+            .collect();
+
+        let (width, height) = (
+            self.header.image_width as usize,
+            self.header.image_height as usize,
+        );
+        if width < 3 || height < 3 {
+            return Ok(0.0);
+        }
+        let mut laplacian = Vec::with_capacity((width - 2) * (height - 2));
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let j = y * width + x;
+                laplacian.push(
+                    luma[j - width] + luma[j + width] + luma[j - 1] + luma[j + 1] - 4.0 * luma[j],
+                );
+            }
+        }
+        let n = laplacian.len() as f64;
+        let mean = laplacian.iter().sum::<f64>() / n;
+        Ok(laplacian.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n)
+    }
+
+    /// Split the file into multiple SER files of at most `chunk_size` frames each, named
+    /// `{basename}_part01.ser`, `{basename}_part02.ser`, and so on. Each part has the same
+    /// image parameters as this file and the corresponding timestamps. Returns the names
+    /// of the files written, which is empty if the file has no frames.
+    pub fn split(&self, basename: &str, chunk_size: usize) -> Result<Vec<String>> {
+        if chunk_size == 0 {
+            return Err(SerError::InvalidArgument(
+                "chunk size must be greater than zero",
+            ));
+        }
+        let mut filenames = vec![];
+        for (part, start) in (0..self.header.frame_count).step_by(chunk_size).enumerate() {
+            let end = (start + chunk_size).min(self.header.frame_count);
+            let filename = format!("{}_part{:02}.ser", basename, part + 1);
+            let header = self.header.with_frame_count(end - start);
+            let mut file = File::create(&filename)?;
+            let mut w = SerWriter::new(&mut file, &header)?;
+            for i in start..end {
+                w.write_frame(self.read_frame(i)?)?;
+            }
+            if self.has_timestamps() {
+                w.write_timestamps(&self.timestamps[start..end])?;
+            }
+            w.finish()?;
+            filenames.push(filename);
+        }
+        Ok(filenames)
+    }
+
+    /// Reader over the raw bytes of all frames in order, excluding the header and trailer
+    pub fn image_data_reader(&self) -> Result<impl Read + '_> {
+        self.frame_bytes(0, self.header.frame_count)
+    }
+
+    /// Write the raw bytes of each frame to a separate file in `dir`, named
+    /// `frame_00000.{ext}`, `frame_00001.{ext}`, and so on. The files contain only the
+    /// image data, in the same pixel format and byte order as this file, with no header.
+    /// Returns the paths of the files written.
+    pub fn explode(&self, dir: &Path, ext: &str) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::with_capacity(self.header.frame_count);
+        for (i, frame) in self.frames().enumerate() {
+            let path = dir.join(format!("frame_{:05}.{}", i, ext));
+            fs::write(&path, frame?)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Write every `stride`th frame starting at frame `offset`, with the corresponding
+    /// timestamps, to a new SER file. Returns the number of frames written.
+    pub fn decimate(&self, offset: usize, stride: usize, w: &mut dyn Write) -> Result<usize> {
+        if stride == 0 {
+            return Err(SerError::InvalidArgument(
+                "stride must be greater than zero",
+            ));
+        }
+        let indices: Vec<usize> = (offset..self.header.frame_count).step_by(stride).collect();
+        let header = self.header.with_frame_count(indices.len());
+        let mut writer = SerWriter::new(w, &header)?;
+        for &i in &indices {
+            writer.write_frame(self.read_frame(i)?)?;
+        }
+        if self.has_timestamps() {
+            let timestamps: Vec<u64> = indices.iter().map(|&i| self.timestamps[i]).collect();
+            writer.write_timestamps(&timestamps)?;
+        }
+        writer.finish()?;
+        Ok(indices.len())
+    }
+
+    /// Copy the frames to a new SER file, replacing each frame with the result of
+    /// applying `f` to it, for example to flip, crop, or subtract a bias frame. The image
+    /// parameters of the new file are taken from `header`, which can be `&self.header`
+    /// if the transform does not change the size of the frames. The frame count is taken
+    /// from this file, and the timestamps are copied if there are any. Every frame
+    /// returned by `f` must have the frame size of `header`.
+    pub fn copy_transform<F: FnMut(&[u8]) -> Vec<u8>>(
+        &self,
+        header: &SerHeader,
+        w: &mut dyn Write,
+        mut f: F,
+    ) -> Result<()> {
+        let header = header.with_frame_count(self.header.frame_count);
+        let mut writer = SerWriter::new(w, &header)?;
+        for frame in self.frames() {
+            writer.write_frame(&f(frame?))?;
+        }
+        if self.has_timestamps() {
+            writer.write_timestamps(&self.timestamps)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Guess whether 16-bit pixel data with a pixel depth of less than 16 bits is stored
+    /// in the high bits or the low bits of each value, by inspecting up to
+    /// `sample_frames` frames spread evenly through the file. Returns
+    /// `BitAlignment::Unknown` for 8-bit or 16-bit data or when the pixel values are
+    /// ambiguous.
+    pub fn detect_bit_alignment(&self, sample_frames: usize) -> BitAlignment {
+        let depth = self.header.pixel_depth_per_plane;
+        if self.header.bytes_per_pixel() != 2 || depth >= 16 {
+            return BitAlignment::Unknown;
+        }
+        let samples = sample_frames.min(self.header.frame_count);
+        let mut bits = 0_u16;
+        for j in 0..samples {
+            let i = j * self.header.frame_count / samples;
+            if let Ok(pixels) = self.read_frame_u16(i) {
+                bits = pixels.iter().fold(bits, |bits, p| bits | p);
+            }
+        }
+        let low_bits = (1_u16 << (16 - depth)) - 1;
+        let high_bits = !(self.header.max_pixel_value() as u16);
+        let low_used = bits & low_bits != 0;
+        let high_used = bits & high_bits != 0;
+        match (low_used, high_used) {
+            (false, true) => BitAlignment::LeftAligned,
+            (true, false) => BitAlignment::RightAligned,
+            _ => BitAlignment::Unknown,
+        }
+    }
+
+    /// Iterate over the frames in order, paired with the timestamp of each frame if the
+    /// file has timestamps. Call `rev` on the iterator to iterate from the last frame.
+    pub fn timestamped_frames(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Result<(Option<u64>, &[u8])>> + '_ {
+        (0..self.header.frame_count)
+            .map(move |i| Ok((self.frame_timestamp(i), self.read_frame(i)?)))
+    }
+
+    /// Iterate over the frames in order
+    pub fn frames(&self) -> impl Iterator<Item = Result<&[u8]>> + '_ {
+        (0..self.header.frame_count).map(move |i| self.read_frame(i))
+    }
+
+    /// Iterate over the frames in reverse order, from the last frame to the first
+    pub fn frames_rev(&self) -> impl Iterator<Item = Result<&[u8]>> + '_ {
+        (0..self.header.frame_count)
+            .rev()
+            .map(move |i| self.read_frame(i))
+    }
+
+    /// Consume the file and iterate over owned copies of the frames, in order. Frames are
+    /// copied out of the file data on demand, so the iterator can be returned from a
+    /// function or sent to another thread.
+    pub fn into_frame_chunks(self) -> impl Iterator<Item = Result<Vec<u8>>> {
+        (0..self.header.frame_count).map(move |i| self.read_frame(i).map(|frame| frame.to_vec()))
+    }
+}
+
+/// Parse and validate the 178 header bytes of a SER file
+fn parse_header(header_bytes: &[u8], permissive_magic: bool) -> Result<SerHeader> {
+    let magic = &header_bytes[0..14];
+    let valid_magic = if permissive_magic {
+        magic[0..5].eq_ignore_ascii_case(&MAGIC.as_bytes()[0..5])
+    } else {
+        magic == MAGIC.as_bytes()
+    };
+    if !valid_magic {
+        return Err(SerError::BadMagic);
+    }
+
+    let lu_id = parse_u32(&header_bytes[14..18])?;
+
+    let bayer = Bayer::from_code(parse_u32(&header_bytes[18..22])?);
+
+    let endianness = Endianness::from_code(parse_u32(&header_bytes[22..26])?);
+
+    let image_width = parse_u32(&header_bytes[26..30])?;
+    let image_height = parse_u32(&header_bytes[30..34])?;
+    if image_width == 0 || image_height == 0 {
+        return Err(SerError::InvalidImageSize {
+            width: image_width,
+            height: image_height,
+        });
+    }
+    let pixel_depth_per_plane = parse_u32(&header_bytes[34..38])?;
+    if !(1..=16).contains(&pixel_depth_per_plane) {
+        return Err(SerError::InvalidPixelDepth(pixel_depth_per_plane));
+    }
+    let frame_count = parse_u32(&header_bytes[38..42])? as usize;
+    let observer = parse_string(&header_bytes[42..82]);
+    let instrument = parse_string(&header_bytes[82..122]);
+    let telescope = parse_string(&header_bytes[122..162]);
+    let date_time = parse_u64(&header_bytes[162..170])?;
+    let date_time_utc = parse_u64(&header_bytes[170..HEADER_SIZE])?;
+
+    Ok(SerHeader {
+        lu_id,
+        image_height,
+        image_width,
+        frame_count,
+        pixel_depth_per_plane,
+        endianness,
+        bayer,
+        observer,
+        telescope,
+        instrument,
+        date_time,
+        date_time_utc,
+    })
+}
+
+/// Whether the bytes start with the SER magic string, for detecting SER files without
+/// opening them
+pub fn is_ser_file(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC.as_bytes())
+}
+
+/// Replace the header of an existing SER file in place, for example to correct the text
+/// fields or the Bayer encoding, without rewriting the image data. The new header must
+/// have the same image size, pixel depth, number of planes, and frame count as the
+/// existing header.
+pub fn update_header(filename: &str, header: &SerHeader) -> Result<()> {
+    {
+        let existing = SerFile::open(filename)?.header;
+        if existing.image_width != header.image_width
+            || existing.image_height != header.image_height
+            || existing.pixel_depth_per_plane != header.pixel_depth_per_plane
+            || existing.num_planes() != header.num_planes()
+            || existing.frame_count != header.frame_count
+        {
+            return Err(SerError::LayoutMismatch);
+        }
+    }
+    let mut file = OpenOptions::new().write(true).open(filename)?;
+    file.write_all(&encode_header(header, header.frame_count)?)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Correct the frame count in the header of a truncated SER file that declares more
+/// frames than it contains, so that it can be opened without `open_lenient`. The partial
+/// frame at the end of the file, if any, is removed so that it is not read as a
+/// timestamp trailer. Returns the number of whole frames in the file. Files that are not
+/// truncated are not modified.
+pub fn repair_frame_count(filename: &str) -> Result<usize> {
+    let (declared, actual, image_end) = {
+        let ser = SerFile::open_lenient(filename)?;
+        (
+            ser.declared_frame_count,
+            ser.header.frame_count,
+            HEADER_SIZE + ser.header.image_data_bytes(),
+        )
+    };
+    if declared > actual {
+        let mut file = OpenOptions::new().write(true).open(filename)?;
+        file.set_len(image_end as u64)?;
+        file.seek(SeekFrom::Start(38))?;
+        file.write_u32::<LittleEndian>(actual as u32)?;
+        file.flush()?;
+    }
+    Ok(actual)
+}
+
+/// Concatenate the frames of several SER files into a single SER file. All files must
+/// have the same image width, height, pixel depth, endianness, and Bayer encoding. The
+/// header is taken from the first file. Timestamps are written only if every file has
+/// them.
+pub fn concat(files: &[SerFile], w: &mut dyn Write) -> Result<()> {
+    let first = files
+        .first()
+        .ok_or(SerError::InvalidArgument("no files to concatenate"))?;
+    let compatible = |h: &SerHeader| {
+        h.image_width == first.header.image_width
+            && h.image_height == first.header.image_height
+            && h.pixel_depth_per_plane == first.header.pixel_depth_per_plane
+            && h.endianness == first.header.endianness
+            && h.bayer == first.header.bayer
+    };
+    if let Some(i) = files.iter().position(|f| !compatible(&f.header)) {
+        return Err(SerError::IncompatibleFile(i));
+    }
+
+    let header = first
+        .header
+        .with_frame_count(files.iter().map(|f| f.header.frame_count).sum());
+    let mut writer = SerWriter::new(w, &header)?;
+    for file in files {
+        for frame in file.frames() {
+            writer.write_frame(frame?)?;
+        }
+    }
+    if files.iter().all(|f| f.has_timestamps()) {
+        for file in files {
+            writer.write_timestamps(&file.timestamps)?;
+        }
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Convert a timestamp in 100-nanosecond ticks since 0001-01-01 UTC (as used for the
+/// header dates and the per-frame timestamps) to a `DateTime`. Returns `None` for a
+/// zero (unset) timestamp or one that cannot be represented.
+#[cfg(feature = "chrono")]
+pub fn ticks_to_datetime(ticks: u64) -> Option<DateTime<Utc>> {
+    if ticks == 0 {
+        return None;
+    }
+    let secs = (ticks / 10_000_000) as i64 - (UNIX_EPOCH_TICKS / 10_000_000) as i64;
+    let nanos = (ticks % 10_000_000) as u32 * 100;
+    Utc.timestamp_opt(secs, nanos).single()
+}
+
+/// Convert a number of 100-nanosecond ticks to a `Duration`
+fn ticks_to_duration(ticks: u64) -> Duration {
+    Duration::new(ticks / 10_000_000, (ticks % 10_000_000) as u32 * 100)
+}
+
+/// A writer that can also seek, as required by `SerWriter::new_deferred`
+pub trait WriteSeek: Write + Seek {}
+
+impl<T: Write + Seek + ?Sized> WriteSeek for T {}
+
+/// Destination that a `SerWriter` writes to
+enum SerOutput<'a> {
+    Write(&'a mut dyn Write),
+    /// Seekable destination, with the offset of the start of the header
+    Seekable(&'a mut dyn WriteSeek, u64),
+}
+
+impl Write for SerOutput<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            SerOutput::Write(w) => w.write(buf),
+            SerOutput::Seekable(w, _) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            SerOutput::Write(w) => w.flush(),
+            SerOutput::Seekable(w, _) => w.flush(),
+        }
+    }
+}
+
+pub struct SerWriter<'a> {
+    header: &'a SerHeader,
+    w: SerOutput<'a>,
+    /// Number of frames written so far
+    frames_written: usize,
+    /// Checksum of the frames written so far
+    #[cfg(feature = "crc")]
+    crc: crc::Crc32,
+}
+
+impl<'a> SerWriter<'a> {
+    /// Create a writer and write the header. Text fields longer than 40 bytes are
+    /// truncated on a UTF-8 character boundary. Returns an error if the frame count does
+    /// not fit in the header.
+    pub fn new(w: &'a mut dyn Write, header: &'a SerHeader) -> Result<Self> {
+        w.write_all(&encode_header(header, header.frame_count)?)?;
+        Ok(Self {
+            header,
+            w: SerOutput::Write(w),
+            frames_written: 0,
+            #[cfg(feature = "crc")]
+            crc: crc::Crc32::new(),
+        })
+    }
+
+    /// Create a writer for streaming frames when the number of frames is not known up
+    /// front. The `frame_count` in the header is ignored and a placeholder is written
+    /// instead, which is replaced with the number of frames actually written when
+    /// `finish` is called.
+    pub fn new_deferred(w: &'a mut dyn WriteSeek, header: &'a SerHeader) -> Result<Self> {
+        let start = w.stream_position()?;
+        w.write_all(&encode_header(header, 0)?)?;
+        Ok(Self {
+            header,
+            w: SerOutput::Seekable(w, start),
+            frames_written: 0,
+            #[cfg(feature = "crc")]
+            crc: crc::Crc32::new(),
+        })
+    }
+
+    /// Write a frame of raw pixel bytes, which are written as they are. For 16-bit data
+    /// the bytes must already be in the byte order given by the header endianness; use
+    /// `write_frame_u16` to have pixel values encoded in that byte order.
+    pub fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        if self.header.image_frame_size() == frame.len() {
+            self.w.write_all(frame)?;
+            self.frames_written += 1;
+            #[cfg(feature = "crc")]
+            self.crc.update(frame);
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "frame {}: expected {} bytes, got {}",
+                    self.frames_written,
+                    self.header.image_frame_size(),
+                    frame.len()
+                ),
+            ))
+        }
+    }
+
+    /// Write each of the frames, stopping at the first error. Returns the number of frames
+    /// written.
+    pub fn write_frames<'b, I: IntoIterator<Item = &'b [u8]>>(
+        &mut self,
+        frames: I,
+    ) -> io::Result<usize> {
+        let mut count = 0;
+        for frame in frames {
+            self.write_frame(frame)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Write a frame of 16-bit pixel values, encoded using the endianness from the header.
+    /// Returns `SerError::UnsupportedPixelDepth` if the header specifies 8-bit pixels.
+    pub fn write_frame_u16(&mut self, frame: &[u16]) -> Result<()> {
+        if self.header.bytes_per_pixel() != 2 {
+            return Err(SerError::UnsupportedPixelDepth(
+                self.header.pixel_depth_per_plane,
+            ));
+        }
+        Ok(self.write_frame(&encode_u16(frame, &self.header.endianness))?)
+    }
+
+    /// Write the optional trailer containing a UTC timestamp for each frame. The trailer
+    /// is only written if this method is called, and must be called after all frames
+    /// have been written. Passing an empty slice writes nothing, so the file has no
+    /// trailer.
+    pub fn write_timestamps(&mut self, timestamps: &[u64]) -> io::Result<()> {
+        if timestamps.is_empty() {
+            return Ok(());
+        }
+        let mut header_bytes = Vec::with_capacity(8 * timestamps.len());
+        for ts in timestamps {
+            header_bytes.write_u64::<LittleEndian>(*ts)?;
+        }
+        self.w.write_all(&header_bytes)
+    }
+
+    /// Write a timestamp trailer for a constant frame rate, for files created from images
+    /// that have no timing information. The first frame is stamped with `start`, in
+    /// 100 ns ticks since 0001-01-01, and each of the frames written so far is stamped
+    /// `1 / fps` seconds after the previous one. Must be called after all frames have
+    /// been written. Returns an error if a timestamp does not fit in 64 bits.
+    pub fn write_timestamps_at_fps(&mut self, start: u64, fps: f64) -> io::Result<()> {
+        if !(fps.is_finite() && fps > 0.0) {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid frame rate {}", fps),
+            ));
+        }
+        let timestamps = (0..self.frames_written)
+            .map(|i| {
+                let offset = (i as f64 * 10_000_000.0 / fps).round();
+                if offset < u64::MAX as f64 {
+                    start.checked_add(offset as u64)
+                } else {
+                    None
+                }
+            })
+            .collect::<Option<Vec<u64>>>()
+            .ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "timestamps at this frame rate overflow the 64-bit tick count",
+                )
+            })?;
+        self.write_timestamps(&timestamps)
+    }
+
+    /// Write a CRC-32 checksum of the image data, which can be checked with
+    /// `SerFile::check_crc`. This is an extension to the SER format, so it must be written
+    /// last, after the timestamp trailer if there is one. Strict parsers ignore the extra
+    /// four bytes at the end of the file.
+    #[cfg(feature = "crc")]
+    pub fn write_crc(&mut self) -> io::Result<()> {
+        self.w.write_u32::<LittleEndian>(self.crc.finish())
+    }
+
+    /// Number of frames written so far
+    pub fn frames_written(&self) -> usize {
+        self.frames_written
+    }
+
+    /// Complete writing the file and flush the underlying writer. For a writer created
+    /// with `new_deferred`, this updates the frame count in the header with the number of
+    /// frames written. Otherwise, this returns an error if the number of frames written
+    /// does not match the header. When writing to a `File`, call `File::sync_all`
+    /// afterwards to ensure that the data has reached the disk.
+    pub fn finish(mut self) -> io::Result<()> {
+        let frames_written = self.frames_written;
+        if let SerOutput::Seekable(w, start) = &mut self.w {
+            let frame_count = u32::try_from(frames_written)
+                .map_err(|_| SerError::FrameCountOverflow(frames_written))?;
+            let end = w.stream_position()?;
+            w.seek(SeekFrom::Start(*start + 38))?;
+            w.write_u32::<LittleEndian>(frame_count)?;
+            w.seek(SeekFrom::Start(end))?;
+        }
+        self.w.flush()?;
+        match self.w {
+            SerOutput::Write(_) if self.frames_written != self.header.frame_count => {
+                Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Wrote {} frames when header specifies frame count as {}",
+                        self.frames_written, self.header.frame_count
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Appends frames to an existing SER file.
+///
+/// Appended frames are written over the timestamp trailer, if present, and `finish`
+/// writes the trailer again after the appended frames, removes any remaining bytes, and
+/// updates the frame count in the header. The file is unchanged until the first frame is
+/// appended, and `finish` leaves it unchanged if no frames were appended. An incomplete
+/// trailer is discarded. With the `crc` feature, a checksum written by
+/// `SerWriter::write_crc` is checked when the appender is opened and is updated to cover
+/// the appended frames; without it, the checksum is discarded.
+pub struct SerAppender {
+    file: File,
+    header: SerHeader,
+    /// Timestamps of existing and appended frames, or `None` if the file has no trailer
+    timestamps: Option<Vec<u64>>,
+    /// Number of frames appended so far
+    frames_appended: usize,
+    /// Checksum of existing and appended frames, or `None` if the file has no checksum
+    #[cfg(feature = "crc")]
+    crc: Option<crc::Crc32>,
+}
+
+impl SerAppender {
+    /// Open an existing SER file for appending frames
+    pub fn open(filename: &str) -> Result<Self> {
+        let ser = SerFile::open(filename)?;
+        #[cfg(feature = "crc")]
+        let crc = if ser.checksum_bytes() > 0 {
+            let crc = ser.image_data_crc();
+            if crc.finish() != parse_u32(&ser.data[ser.data.len() - 4..])? {
+                return Err(SerError::ChecksumMismatch);
+            }
+            Some(crc)
+        } else {
+            None
+        };
+        let timestamps = if ser.has_timestamps() {
+            Some(ser.timestamps)
+        } else {
+            None
+        };
+        let header = ser.header;
+        // release the memory map before modifying the file
+        drop(ser.data);
+
+        let mut file = OpenOptions::new().read(true).write(true).open(filename)?;
+        let image_end = (HEADER_SIZE + header.image_data_bytes()) as u64;
+        file.seek(SeekFrom::Start(image_end))?;
+
+        Ok(Self {
+            file,
+            header,
+            timestamps,
+            frames_appended: 0,
+            #[cfg(feature = "crc")]
+            crc,
+        })
+    }
+
+    /// Header of the file being appended to, including the frames appended so far
+    pub fn header(&self) -> &SerHeader {
+        &self.header
+    }
+
+    /// Append a frame, which must match the frame size of the file. A timestamp must be
+    /// supplied if and only if the file has a timestamp trailer.
+    pub fn append_frame(&mut self, frame: &[u8], timestamp: Option<u64>) -> Result<()> {
+        if frame.len() != self.header.image_frame_size() {
+            return Err(SerError::BufferSizeMismatch {
+                expected: self.header.image_frame_size(),
+                actual: frame.len(),
+            });
+        }
+        match (&self.timestamps, timestamp) {
+            (Some(_), Some(_)) | (None, None) => {}
+            (None, Some(_)) if self.header.frame_count == 0 => {}
+            _ => return Err(SerError::InconsistentTimestamps),
+        }
+        self.file.write_all(frame)?;
+        #[cfg(feature = "crc")]
+        if let Some(crc) = &mut self.crc {
+            crc.update(frame);
+        }
+        if let Some(ts) = timestamp {
+            self.timestamps.get_or_insert_with(Vec::new).push(ts);
+        }
+        self.header.frame_count += 1;
+        self.frames_appended += 1;
+        Ok(())
+    }
+
+    /// Write the timestamp trailer and update the frame count in the header. This does
+    /// nothing if no frames were appended.
+    pub fn finish(mut self) -> Result<()> {
+        if self.frames_appended == 0 {
+            return Ok(());
+        }
+        let frame_count = u32::try_from(self.header.frame_count)
+            .map_err(|_| SerError::FrameCountOverflow(self.header.frame_count))?;
+        if let Some(timestamps) = &self.timestamps {
+            let mut trailer_bytes = Vec::with_capacity(8 * timestamps.len());
+            for ts in timestamps {
+                trailer_bytes.write_u64::<LittleEndian>(*ts)?;
+            }
+            self.file.write_all(&trailer_bytes)?;
+        }
+        #[cfg(feature = "crc")]
+        if let Some(crc) = self.crc {
+            self.file.write_u32::<LittleEndian>(crc.finish())?;
+        }
+        let end = self.file.stream_position()?;
+        self.file.set_len(end)?;
+        self.file.seek(SeekFrom::Start(38))?;
+        self.file.write_u32::<LittleEndian>(frame_count)?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes a SER file through a memory mapping, avoiding a system call per frame when
+/// writing large files.
+///
+/// The file is created with space for the header, every frame declared in the header,
+/// and the timestamp trailer. Frames are copied into the mapping in order. The trailer is
+/// removed from the file by `finish` if `write_timestamps` was not called.
+#[cfg(feature = "mmap")]
+pub struct SerMmapWriter {
+    file: File,
+    mmap: MmapMut,
+    header: SerHeader,
+    /// Number of frames written so far
+    frames_written: usize,
+    /// Whether the timestamp trailer has been written
+    timestamps_written: bool,
+}
+
+#[cfg(feature = "mmap")]
+impl SerMmapWriter {
+    /// Create the file, replacing any existing file, and write the header
+    pub fn create(filename: &str, header: &SerHeader) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(filename)?;
+        let len = header
+            .checked_image_data_bytes()
+            .and_then(|bytes| bytes.checked_add(HEADER_SIZE))
+            .and_then(|bytes| bytes.checked_add(header.frame_count.checked_mul(8)?))
+            .ok_or(SerError::SizeOverflow)?;
+        file.set_len(len as u64)?;
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        mmap[0..HEADER_SIZE].copy_from_slice(&encode_header(header, header.frame_count)?);
+        Ok(Self {
+            file,
+            mmap,
+            header: header.clone(),
+            frames_written: 0,
+            timestamps_written: false,
+        })
+    }
+
+    /// Write the next frame, which must match the frame size of the header
+    pub fn write_frame(&mut self, frame: &[u8]) -> Result<()> {
+        let frame_size = self.header.image_frame_size();
+        if frame.len() != frame_size {
+            return Err(SerError::BufferSizeMismatch {
+                expected: frame_size,
+                actual: frame.len(),
+            });
+        }
+        if self.frames_written == self.header.frame_count {
+            return Err(SerError::InvalidFrameIndex {
+                index: self.frames_written,
+                frame_count: self.header.frame_count,
+            });
+        }
+        let offset = HEADER_SIZE + self.frames_written * frame_size;
+        self.mmap[offset..offset + frame_size].copy_from_slice(frame);
+        self.frames_written += 1;
+        Ok(())
+    }
+
+    /// Write the next frame of 16-bit pixel values, encoded using the endianness from the
+    /// header
+    pub fn write_frame_u16(&mut self, frame: &[u16]) -> Result<()> {
+        if self.header.bytes_per_pixel() != 2 {
+            return Err(SerError::UnsupportedPixelDepth(
+                self.header.pixel_depth_per_plane,
+            ));
+        }
+        self.write_frame(&encode_u16(frame, &self.header.endianness))
+    }
+
+    /// Write the timestamp trailer, which must contain a timestamp for every frame
+    /// declared in the header
+    pub fn write_timestamps(&mut self, timestamps: &[u64]) -> Result<()> {
+        if timestamps.len() != self.header.frame_count {
+            return Err(SerError::InvalidTrailer {
+                expected: 8 * self.header.frame_count,
+                actual: 8 * timestamps.len(),
+            });
+        }
+        let trailer_offset = HEADER_SIZE + self.header.image_data_bytes();
+        for (i, ts) in timestamps.iter().enumerate() {
+            let offset = trailer_offset + i * 8;
+            LittleEndian::write_u64(&mut self.mmap[offset..offset + 8], *ts);
+        }
+        self.timestamps_written = true;
+        Ok(())
+    }
+
+    /// Number of frames written so far
+    pub fn frames_written(&self) -> usize {
+        self.frames_written
+    }
+
+    /// Flush the mapping to the file, removing the trailer if no timestamps were written.
+    /// Returns an error, leaving the file at its full size, if fewer frames were written
+    /// than the header specifies.
+    pub fn finish(self) -> Result<()> {
+        if self.frames_written != self.header.frame_count {
+            return Err(SerError::FrameCountMismatch {
+                expected: self.header.frame_count,
+                written: self.frames_written,
+            });
+        }
+        self.mmap.flush()?;
+        drop(self.mmap);
+        if !self.timestamps_written {
+            self.file
+                .set_len((HEADER_SIZE + self.header.image_data_bytes()) as u64)?;
+        }
+        Ok(())
+    }
+}
+
+/// Encode 16-bit pixel values as bytes in the given byte order
+fn encode_u16(pixels: &[u16], endianness: &Endianness) -> Vec<u8> {
+    let mut bytes = vec![0_u8; pixels.len() * 2];
+    match endianness {
+        Endianness::LittleEndian => LittleEndian::write_u16_into(pixels, &mut bytes),
+        Endianness::BigEndian => BigEndian::write_u16_into(pixels, &mut bytes),
+    }
+    bytes
+}
+
+/// Encode a SER header with the given frame count. Text fields longer than 40 bytes are
+/// truncated, and a frame count that does not fit in 32 bits is rejected. Returns
+/// `SerError::HeaderSizeMismatch` rather than writing a malformed header if the encoded
+/// fields do not add up to `HEADER_SIZE` bytes.
+fn encode_header(header: &SerHeader, frame_count: usize) -> Result<Vec<u8>> {
+    let frame_count =
+        u32::try_from(frame_count).map_err(|_| SerError::FrameCountOverflow(frame_count))?;
+    let mut header_bytes: Vec<u8> = Vec::with_capacity(HEADER_SIZE);
+    header_bytes.append(&mut MAGIC.as_bytes().to_vec());
+    header_bytes.write_u32::<LittleEndian>(header.lu_id)?;
+    header_bytes.write_u32::<LittleEndian>(header.bayer.to_code())?;
+    header_bytes.write_u32::<LittleEndian>(header.endianness.to_code())?;
+    header_bytes.write_u32::<LittleEndian>(header.image_width)?;
+    header_bytes.write_u32::<LittleEndian>(header.image_height)?;
+    header_bytes.write_u32::<LittleEndian>(header.pixel_depth_per_plane)?;
+    header_bytes.write_u32::<LittleEndian>(frame_count)?;
+
+    header_bytes.write_all(&encode_string(&header.observer, 40))?;
+    header_bytes.write_all(&encode_string(&header.instrument, 40))?;
+    header_bytes.write_all(&encode_string(&header.telescope, 40))?;
+
+    header_bytes.write_u64::<LittleEndian>(header.date_time)?;
+    header_bytes.write_u64::<LittleEndian>(header.date_time_utc)?;
+
+    if header_bytes.len() != HEADER_SIZE {
+        return Err(SerError::HeaderSizeMismatch {
+            expected: HEADER_SIZE,
+            actual: header_bytes.len(),
+        });
+    }
+
+    Ok(header_bytes)
+}
+
+/// Category of the image data in a SER file, independent of the specific Bayer pattern
+/// or color order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorType {
+    /// Single-channel monochrome data
+    Mono,
+    /// Raw data from a sensor with a color filter array, in any of the Bayer patterns
+    Bayer,
+    /// Interleaved three-channel color data, in RGB or BGR order
+    Rgb,
+    /// Unrecognized color code
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Bayer {
+    Mono,
+    RGGB,
+    GRBG,
+    GBRG,
+    BGGR,
+    CYYM,
+    YCMY,
+    YMCY,
+    MYYC,
+    RGB,
+    BGR,
+    Unknown(u32),
+}
+
+impl Bayer {
+    /// Convert the code stored in the SER header to a `Bayer` value
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            0 => Bayer::Mono,
+            8 => Bayer::RGGB,
+            9 => Bayer::GRBG,
+            10 => Bayer::GBRG,
+            11 => Bayer::BGGR,
+            16 => Bayer::CYYM,
+            17 => Bayer::YCMY,
+            18 => Bayer::YMCY,
+            19 => Bayer::MYYC,
+            100 => Bayer::RGB,
+            101 => Bayer::BGR,
+            _ => Bayer::Unknown(code),
+        }
+    }
+
+    /// Convert to the code stored in the SER header
+    pub fn to_code(&self) -> u32 {
+        match self {
+            Bayer::Mono => 0,
+            Bayer::RGGB => 8,
+            Bayer::GRBG => 9,
+            Bayer::GBRG => 10,
+            Bayer::BGGR => 11,
+            Bayer::CYYM => 16,
+            Bayer::YCMY => 17,
+            Bayer::YMCY => 18,
+            Bayer::MYYC => 19,
+            Bayer::RGB => 100,
+            Bayer::BGR => 101,
+            Bayer::Unknown(code) => *code,
+        }
+    }
+}
+
+impl fmt::Display for Bayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bayer::Unknown(code) => write!(f, "Unknown(0x{:x})", code),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl FromStr for Bayer {
+    type Err = ParseBayerError;
+
+    /// Parse the name of a Bayer encoding, such as `"RGGB"`, ignoring case
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "MONO" => Ok(Bayer::Mono),
+            "RGGB" => Ok(Bayer::RGGB),
+            "GRBG" => Ok(Bayer::GRBG),
+            "GBRG" => Ok(Bayer::GBRG),
+            "BGGR" => Ok(Bayer::BGGR),
+            "CYYM" => Ok(Bayer::CYYM),
+            "YCMY" => Ok(Bayer::YCMY),
+            "YMCY" => Ok(Bayer::YMCY),
+            "MYYC" => Ok(Bayer::MYYC),
+            "RGB" => Ok(Bayer::RGB),
+            "BGR" => Ok(Bayer::BGR),
+            _ => Err(ParseBayerError(s.to_string())),
+        }
+    }
+}
+
+/// Error returned when parsing an unrecognized Bayer encoding name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBayerError(String);
+
+impl fmt::Display for ParseBayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unrecognized Bayer encoding '{}'", self.0)
+    }
+}
+
+impl error::Error for ParseBayerError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Endianness {
+    LittleEndian,
+    BigEndian,
+}
+
+impl Endianness {
+    /// Convert the code stored in the SER header to an `Endianness` value. Zero means
+    /// little-endian and any other value means big-endian.
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            0 => Endianness::LittleEndian,
+            _ => Endianness::BigEndian,
+        }
+    }
+
+    /// Convert to the code stored in the SER header
+    pub fn to_code(&self) -> u32 {
+        match self {
+            Endianness::LittleEndian => 0,
+            Endianness::BigEndian => 1,
+        }
+    }
+}
+
+impl fmt::Display for Endianness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endianness::LittleEndian => write!(f, "Little-endian"),
+            Endianness::BigEndian => write!(f, "Big-endian"),
+        }
+    }
+}
+
+/// Parse a little-endian u32, returning an error if there are fewer than four bytes
+fn parse_u32(buf: &[u8]) -> Result<u32> {
+    let mut buf = buf;
+    Ok(buf.read_u32::<LittleEndian>()?)
+}
+
+/// Parse a little-endian u64, returning an error if there are fewer than eight bytes
+fn parse_u64(buf: &[u8]) -> Result<u64> {
+    let mut buf = buf;
+    Ok(buf.read_u64::<LittleEndian>()?)
+}
+
+/// Parse a fixed-width string field, replacing invalid UTF-8 and trimming trailing NUL
+/// and whitespace padding
+fn parse_string(x: &[u8]) -> String {
+    String::from_utf8_lossy(x)
+        .trim_end_matches(|c: char| c == '\0' || c.is_ascii_whitespace())
+        .to_string()
+}
+
+/// Encode a string into a fixed-width field, truncating on a UTF-8 character boundary
+/// and padding with NUL bytes
+fn encode_string(x: &str, len: usize) -> Vec<u8> {
+    let mut end = x.len().min(len);
+    while !x.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut buf = x.as_bytes()[..end].to_vec();
+    buf.resize(len, 0);
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Header for an 8-bit mono file
+    fn mono8(width: u32, height: u32, frame_count: usize) -> SerHeader {
+        SerHeaderBuilder::new()
+            .image_size(width, height)
+            .frame_count(frame_count)
+            .build()
+    }
+
+    /// Frames for the header where every byte of frame `i` has the value `i`
+    fn numbered_frames(header: &SerHeader) -> Vec<Vec<u8>> {
+        (0..header.frame_count)
+            .map(|i| vec![i as u8; header.image_frame_size()])
+            .collect()
+    }
+
+    /// Write a SER file into memory
+    fn write_ser(header: &SerHeader, frames: &[Vec<u8>], timestamps: &[u64]) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, header)?;
+        for frame in frames {
+            w.write_frame(frame)?;
+        }
+        w.write_timestamps(timestamps)?;
+        w.finish()?;
+        Ok(bytes)
+    }
+
+    /// Path of a temporary file that is unique to the test process
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("ser-io-{}-{}", std::process::id(), name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Open SER file bytes from memory
+    fn open_ser(bytes: Vec<u8>) -> Result<SerFile> {
+        SerFile::from_bytes(bytes)
+    }
+
+    #[test]
+    fn round_trip_timestamps() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let timestamps = [100, 200, 300];
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &timestamps)?)?;
+        assert_eq!(ser.timestamps.len(), 3);
+        for (i, ts) in timestamps.iter().enumerate() {
+            assert_eq!(ser.timestamps[i], *ts);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn write_timestamps_and_reopen() -> Result<()> {
+        let header = mono8(4, 2, 4);
+        let input = vec![
+            637_450_560_000_000_000,
+            637_450_560_000_100_000,
+            637_450_560_000_200_000,
+            637_450_560_000_300_000,
+        ];
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &input)?)?;
+        assert_eq!(ser.timestamps, input);
+        Ok(())
+    }
+
+    #[test]
+    fn rgb_frame_size() -> Result<()> {
+        let header = SerHeader {
+            pixel_depth_per_plane: 16,
+            bayer: Bayer::RGB,
+            ..mono8(4, 3, 2)
+        };
+        assert_eq!(header.num_planes(), 3);
+        assert_eq!(header.image_frame_size(), 4 * 3 * 3 * 2);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert_eq!(ser.read_frame(1)?.len(), 4 * 3 * 3 * 2);
+        Ok(())
+    }
+
+    #[test]
+    fn header_strings_are_trimmed() {
+        let mut field = b"SkyCam".to_vec();
+        field.resize(40, 0);
+        assert_eq!(parse_string(&field), "SkyCam");
+        assert_eq!(parse_string(b"SkyCam  \0\0"), "SkyCam");
+        assert_eq!(parse_string(&[0; 40]), "");
+    }
+
+    #[test]
+    fn header_text_fields_have_fixed_width() -> Result<()> {
+        let exact = "x".repeat(40);
+        let long = "y".repeat(50);
+        let multibyte = "é".repeat(30);
+        let cases = [
+            ("Me", "Me".to_string()),
+            (exact.as_str(), exact.clone()),
+            (long.as_str(), "y".repeat(40)),
+            (multibyte.as_str(), "é".repeat(20)),
+        ];
+        for (observer, expected) in cases.iter() {
+            let header = SerHeader {
+                observer: observer.to_string(),
+                ..mono8(1, 1, 0)
+            };
+            let bytes = write_ser(&header, &[], &[])?;
+            assert_eq!(bytes.len(), HEADER_SIZE);
+            assert_eq!(&open_ser(bytes)?.header.observer, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn frames_iterator() -> Result<()> {
+        let header = mono8(2, 2, 4);
+        let frames = numbered_frames(&header);
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        let read = ser.frames().collect::<Result<Vec<_>>>()?;
+        assert_eq!(read.len(), ser.header.frame_count);
+        for (frame, expected) in read.iter().zip(frames.iter()) {
+            assert_eq!(*frame, expected.as_slice());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn read_frame_u16_respects_endianness() -> Result<()> {
+        let frame = vec![0x34, 0x12, 0x78, 0x56];
+        let little = SerHeader {
+            pixel_depth_per_plane: 16,
+            ..mono8(2, 1, 1)
+        };
+        let ser = open_ser(write_ser(&little, std::slice::from_ref(&frame), &[])?)?;
+        assert_eq!(ser.read_frame_u16(0)?, vec![0x1234, 0x5678]);
+
+        let big = SerHeader {
+            endianness: Endianness::BigEndian,
+            ..little
+        };
+        let ser = open_ser(write_ser(&big, &[frame], &[])?)?;
+        assert_eq!(ser.read_frame_u16(0)?, vec![0x3412, 0x7856]);
+
+        let header = mono8(2, 1, 1);
+        let ser = open_ser(write_ser(&header, &[vec![7, 255]], &[])?)?;
+        assert_eq!(ser.read_frame_u16(0)?, vec![7, 255]);
+        Ok(())
+    }
+
+    #[test]
+    fn open_reports_typed_errors() -> Result<()> {
+        assert!(matches!(open_ser(vec![0; 10]), Err(SerError::FileTooShort)));
+        assert!(matches!(
+            open_ser(vec![0; HEADER_SIZE]),
+            Err(SerError::BadMagic)
+        ));
+
+        let header = mono8(2, 2, 2);
+        let mut bytes = write_ser(&header, &numbered_frames(&header), &[])?;
+        bytes.truncate(HEADER_SIZE + 6);
+        match open_ser(bytes) {
+            Err(SerError::TruncatedImageData { expected, actual }) => {
+                assert_eq!(expected, HEADER_SIZE + 8);
+                assert_eq!(actual, HEADER_SIZE + 6);
+            }
+            _ => panic!("expected a truncated image data error"),
+        }
+
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert!(matches!(
+            ser.read_frame(2),
+            Err(SerError::InvalidFrameIndex {
+                index: 2,
+                frame_count: 2
+            })
+        ));
+        Ok(())
+    }
+
+    /// 2021-01-01T00:00:00Z in 100 ns ticks since 0001-01-01
+    const TICKS_2021: u64 = 637_450_560_000_000_000;
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn ticks_convert_to_datetime() {
+        use chrono::SecondsFormat;
+
+        assert_eq!(ticks_to_datetime(0), None);
+        assert_eq!(
+            ticks_to_datetime(UNIX_EPOCH_TICKS),
+            Utc.timestamp_opt(0, 0).single()
+        );
+        assert_eq!(
+            ticks_to_datetime(UNIX_EPOCH_TICKS + 15_000_000),
+            Utc.timestamp_opt(1, 500_000_000).single()
+        );
+        assert_eq!(
+            ticks_to_datetime(TICKS_2021),
+            Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).single()
+        );
+        assert_eq!(
+            ticks_to_datetime(637_185_764_967_890_000)
+                .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            Some("2020-02-29T12:34:56.789Z".to_string())
+        );
+        let header = SerHeader {
+            date_time_utc: TICKS_2021,
+            ..mono8(1, 1, 0)
+        };
+        assert_eq!(
+            header.date_time_utc_as_datetime(),
+            ticks_to_datetime(TICKS_2021)
+        );
+    }
+
+    /// Bytes of a file whose header declares `declared` 2x2 mono frames but which only
+    /// contains `present` whole frames followed by part of another frame
+    fn truncated_ser(declared: usize, present: usize) -> Result<Vec<u8>> {
+        let mut bytes = encode_header(&mono8(2, 2, declared), declared)?;
+        for i in 0..present {
+            bytes.extend_from_slice(&[i as u8; 4]);
+        }
+        bytes.extend_from_slice(&[0xff; 2]);
+        Ok(bytes)
+    }
+
+    #[test]
+    fn open_lenient_recovers_whole_frames() -> Result<()> {
+        let path = temp_path("lenient.ser");
+        fs::write(&path, truncated_ser(10, 6)?)?;
+        assert!(matches!(
+            SerFile::open(&path),
+            Err(SerError::TruncatedImageData { .. })
+        ));
+        let ser = SerFile::open_lenient(&path)?;
+        assert_eq!(ser.header.frame_count, 6);
+        assert_eq!(ser.declared_frame_count, 10);
+        assert_eq!(ser.frames().count(), 6);
+        assert_eq!(ser.read_frame(5)?, &[5; 4]);
+        assert!(ser.read_frame(6).is_err());
+        assert!(ser.timestamps.is_empty());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn open_from_reader() -> Result<()> {
+        let header = mono8(3, 2, 3);
+        let frames = numbered_frames(&header);
+        let bytes = write_ser(&header, &frames, &[1, 2, 3])?;
+        let ser = SerFile::open_from_reader(io::Cursor::new(bytes))?;
+        assert_eq!(ser.header.image_width, 3);
+        assert_eq!(ser.header.image_height, 2);
+        assert_eq!(ser.header.frame_count, 3);
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(ser.read_frame(i)?, frame.as_slice());
+        }
+        assert_eq!(ser.timestamps, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn read_frame_native_swaps_when_needed() -> Result<()> {
+        let big = SerHeader {
+            pixel_depth_per_plane: 16,
+            endianness: Endianness::BigEndian,
+            ..mono8(2, 1, 1)
+        };
+        let ser = open_ser(write_ser(&big, &[vec![0x12, 0x34, 0xab, 0xcd]], &[])?)?;
+        assert_eq!(ser.read_frame_native(0)?, vec![0x1234, 0xabcd]);
+
+        let little = SerHeader {
+            endianness: Endianness::LittleEndian,
+            ..big
+        };
+        let ser = open_ser(write_ser(&little, &[vec![0x34, 0x12, 0xcd, 0xab]], &[])?)?;
+        assert_eq!(ser.read_frame_native(0)?, vec![0x1234, 0xabcd]);
+
+        let header = mono8(2, 1, 1);
+        let ser = open_ser(write_ser(&header, &[vec![1, 2]], &[])?)?;
+        assert!(matches!(
+            ser.read_frame_native(0),
+            Err(SerError::UnsupportedPixelDepth(8))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn frame_timestamp_accessor() -> Result<()> {
+        let header = mono8(2, 2, 2);
+        let frames = numbered_frames(&header);
+        let ser = open_ser(write_ser(&header, &frames, &[10, 20])?)?;
+        assert!(ser.has_timestamps());
+        assert_eq!(ser.frame_timestamp(0), Some(10));
+        assert_eq!(ser.frame_timestamp(1), Some(20));
+        assert_eq!(ser.frame_timestamp(2), None);
+
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        assert!(!ser.has_timestamps());
+        assert_eq!(ser.frame_timestamp(0), None);
+        Ok(())
+    }
+
+    #[test]
+    fn builder_round_trip() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(3, 2)
+            .pixel_depth(12)
+            .frame_count(2)
+            .endianness(Endianness::BigEndian)
+            .bayer(Bayer::GBRG)
+            .observer("Observer")
+            .telescope("Telescope")
+            .instrument("Instrument")
+            .date_time(TICKS_2021 + 1)
+            .date_time_utc(TICKS_2021)
+            .build();
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let read = &ser.header;
+        assert_eq!((read.image_width, read.image_height), (3, 2));
+        assert_eq!(read.pixel_depth_per_plane, 12);
+        assert_eq!(read.frame_count, 2);
+        assert!(matches!(read.endianness, Endianness::BigEndian));
+        assert!(matches!(read.bayer, Bayer::GBRG));
+        assert_eq!(read.observer, "Observer");
+        assert_eq!(read.telescope, "Telescope");
+        assert_eq!(read.instrument, "Instrument");
+        assert_eq!(read.date_time, TICKS_2021 + 1);
+        assert_eq!(read.date_time_utc, TICKS_2021);
+        Ok(())
+    }
+
+    #[test]
+    fn deferred_frame_count() -> Result<()> {
+        let header = mono8(2, 2, 99);
+        let mut cursor = io::Cursor::new(vec![]);
+        let mut w = SerWriter::new_deferred(&mut cursor, &header)?;
+        for i in 0..3 {
+            w.write_frame(&[i; 4])?;
+        }
+        w.finish()?;
+        let ser = open_ser(cursor.into_inner())?;
+        assert_eq!(ser.header.frame_count, 3);
+        assert_eq!(ser.read_frame(2)?, &[2; 4]);
+        Ok(())
+    }
+
+    #[test]
+    fn finish_checks_frame_count() -> Result<()> {
+        let header = mono8(1, 1, 2);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        w.write_frame(&[1])?;
+        w.write_frame(&[2])?;
+        assert_eq!(w.frames_written(), 2);
+        w.finish()?;
+
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        w.write_frame(&[1])?;
+        assert_eq!(w.frames_written(), 1);
+        assert!(w.finish().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn header_clone_and_compare() {
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 2)
+            .bayer(Bayer::RGGB)
+            .observer("Observer")
+            .build();
+        let copy = header.clone();
+        assert_eq!(copy, header);
+        let other = SerHeader {
+            endianness: Endianness::BigEndian,
+            ..header.clone()
+        };
+        assert_ne!(other, header);
+        assert_eq!(Bayer::Unknown(3).clone(), Bayer::Unknown(3));
+        assert_ne!(Bayer::Unknown(3), Bayer::Unknown(4));
+    }
+
+    #[test]
+    fn bayer_code_round_trip() {
+        let variants = [
+            Bayer::Mono,
+            Bayer::RGGB,
+            Bayer::GRBG,
+            Bayer::GBRG,
+            Bayer::BGGR,
+            Bayer::CYYM,
+            Bayer::YCMY,
+            Bayer::YMCY,
+            Bayer::MYYC,
+            Bayer::RGB,
+            Bayer::BGR,
+            Bayer::Unknown(12345),
+        ];
+        for b in variants.iter() {
+            assert_eq!(&Bayer::from_code(b.to_code()), b);
+        }
+        assert_eq!(Bayer::from_code(8), Bayer::RGGB);
+        assert_eq!(Bayer::BGR.to_code(), 101);
+    }
+
+    #[test]
+    fn endianness_codes() -> Result<()> {
+        assert_eq!(Endianness::LittleEndian.to_code(), 0);
+        assert_eq!(Endianness::BigEndian.to_code(), 1);
+        assert_eq!(Endianness::from_code(0), Endianness::LittleEndian);
+        assert_eq!(Endianness::from_code(1), Endianness::BigEndian);
+        assert_eq!(Endianness::from_code(7), Endianness::BigEndian);
+
+        let header = SerHeaderBuilder::new()
+            .image_size(1, 1)
+            .pixel_depth(16)
+            .endianness(Endianness::BigEndian)
+            .build();
+        let bytes = encode_header(&header, 0)?;
+        assert_eq!(parse_u32(&bytes[22..26])?, 1);
+        assert_eq!(open_ser(bytes)?.header.endianness, Endianness::BigEndian);
+        Ok(())
+    }
+
+    #[test]
+    fn color_classification() {
+        let header = |bayer| SerHeaderBuilder::new().bayer(bayer).build();
+        let mono = header(Bayer::Mono);
+        assert!(!mono.is_color());
+        assert!(!mono.is_bayer());
+        assert_eq!(mono.channels(), 1);
+        let rggb = header(Bayer::RGGB);
+        assert!(rggb.is_color());
+        assert!(rggb.is_bayer());
+        assert_eq!(rggb.channels(), 1);
+        let rgb = header(Bayer::RGB);
+        assert!(rgb.is_color());
+        assert!(!rgb.is_bayer());
+        assert_eq!(rgb.channels(), 3);
+    }
+
+    #[test]
+    fn read_frame_into_buffer() -> Result<()> {
+        let header = mono8(2, 2, 2);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let mut buf = [0; 4];
+        ser.read_frame_into(1, &mut buf)?;
+        assert_eq!(buf, [1; 4]);
+        let mut small = [0; 3];
+        assert!(matches!(
+            ser.read_frame_into(1, &mut small),
+            Err(SerError::BufferSizeMismatch {
+                expected: 4,
+                actual: 3
+            })
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn header_json_round_trip() {
+        let header = SerHeaderBuilder::new()
+            .image_size(640, 480)
+            .pixel_depth(12)
+            .frame_count(10)
+            .endianness(Endianness::BigEndian)
+            .bayer(Bayer::Unknown(42))
+            .observer("Observer")
+            .date_time_utc(TICKS_2021)
+            .build();
+        let json = serde_json::to_string(&header).unwrap();
+        assert!(json.contains(r#""bayer":{"Unknown":42}"#));
+        assert!(json.contains(r#""endianness":"BigEndian""#));
+        let parsed: SerHeader = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, header);
+    }
+
+    #[test]
+    fn invalid_pixel_depth() -> Result<()> {
+        for depth in [0, 64].iter() {
+            let header = SerHeaderBuilder::new()
+                .image_size(2, 2)
+                .pixel_depth(*depth)
+                .build();
+            let bytes = encode_header(&header, 0)?;
+            assert!(matches!(
+                open_ser(bytes),
+                Err(SerError::InvalidPixelDepth(d)) if d == *depth
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn frame_offsets() -> Result<()> {
+        let header = mono8(3, 2, 6);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert_eq!(ser.frame_offset(0), Some(HEADER_SIZE));
+        assert_eq!(ser.frame_offset(5), Some(HEADER_SIZE + 5 * 6));
+        assert_eq!(ser.frame_offset(6), None);
+        Ok(())
+    }
+
+    #[test]
+    fn append_frames_with_timestamps() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let path = temp_path("append.ser");
+        fs::write(
+            &path,
+            write_ser(&header, &numbered_frames(&header), &[10, 20, 30])?,
+        )?;
+        let mut appender = SerAppender::open(&path)?;
+        appender.append_frame(&[3; 4], Some(40))?;
+        appender.append_frame(&[4; 4], Some(50))?;
+        assert!(matches!(
+            appender.append_frame(&[5; 4], None),
+            Err(SerError::InconsistentTimestamps)
+        ));
+        assert!(matches!(
+            appender.append_frame(&[5; 3], Some(60)),
+            Err(SerError::BufferSizeMismatch { .. })
+        ));
+        assert_eq!(appender.header().frame_count, 5);
+        appender.finish()?;
+
+        let ser = SerFile::open(&path)?;
+        assert_eq!(ser.header.frame_count, 5);
+        for i in 0..5 {
+            assert_eq!(ser.read_frame(i)?, &[i as u8; 4]);
+        }
+        assert_eq!(ser.timestamps, vec![10, 20, 30, 40, 50]);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn append_frames_without_timestamps() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let path = temp_path("append-no-trailer.ser");
+        fs::write(&path, write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let mut appender = SerAppender::open(&path)?;
+        assert!(matches!(
+            appender.append_frame(&[3; 4], Some(40)),
+            Err(SerError::InconsistentTimestamps)
+        ));
+        appender.append_frame(&[3; 4], None)?;
+        appender.finish()?;
+
+        let ser = SerFile::open(&path)?;
+        assert_eq!(ser.header.frame_count, 4);
+        assert_eq!(ser.read_frame(3)?, &[3; 4]);
+        assert!(ser.timestamps.is_empty());
+        assert_eq!(fs::metadata(&path)?.len() as usize, HEADER_SIZE + 16);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
 
-/// Parse a string
-fn parse_string(x: &[u8]) -> String {
-    str::from_utf8(x).unwrap_or("").to_string()
+    #[test]
+    fn finish_without_appending_leaves_file_unchanged() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let path = temp_path("append-nothing.ser");
+        let bytes = write_ser(&header, &numbered_frames(&header), &[10, 20, 30])?;
+        fs::write(&path, &bytes)?;
+        SerAppender::open(&path)?.finish()?;
+        assert_eq!(fs::read(&path)?, bytes);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn verify_file() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let mut bytes = write_ser(&header, &frames, &[10, 20, 30])?;
+        open_ser(bytes.clone())?.verify()?;
+
+        bytes.truncate(bytes.len() - 8);
+        assert!(matches!(
+            open_ser(bytes)?.verify(),
+            Err(SerError::InvalidTrailer {
+                expected: 24,
+                actual: 16
+            })
+        ));
+
+        let ser = open_ser(write_ser(&header, &frames, &[10, 30, 20])?)?;
+        assert!(matches!(
+            ser.verify(),
+            Err(SerError::TimestampsNotMonotonic(2))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn duration_and_frame_rate() -> Result<()> {
+        let header = mono8(1, 1, 5);
+        let frames = numbered_frames(&header);
+        let timestamps: Vec<u64> = (0..5).map(|i| TICKS_2021 + i * 1_000_000).collect();
+        let ser = open_ser(write_ser(&header, &frames, &timestamps)?)?;
+        assert_eq!(ser.duration(), Some(Duration::from_millis(400)));
+        assert!((ser.average_fps().unwrap() - 10.0).abs() < 1e-9);
+
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        assert_eq!(ser.duration(), None);
+        assert_eq!(ser.average_fps(), None);
+        assert_eq!(ticks_to_duration(10_000_015), Duration::new(1, 1500));
+        Ok(())
+    }
+
+    #[test]
+    fn write_single_frame() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let mut out = vec![];
+        ser.write_frame_to(2, &mut out)?;
+        assert_eq!(out, ser.read_frame(2)?);
+        assert!(ser.write_frame_to(3, &mut out).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_frames_concurrently() -> Result<()> {
+        let header = mono8(4, 4, 8);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let sum = |frames: std::ops::Range<usize>| -> u64 {
+            frames
+                .map(|i| {
+                    ser.read_frame(i)
+                        .unwrap()
+                        .iter()
+                        .map(|&p| p as u64)
+                        .sum::<u64>()
+                })
+                .sum()
+        };
+        let sequential = sum(0..8);
+        let parallel: u64 = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|t| s.spawn(move || sum(2 * t..2 * t + 2)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(parallel, sequential);
+        Ok(())
+    }
+
+    #[test]
+    fn read_frame_roi() -> Result<()> {
+        let header = mono8(4, 3, 1);
+        let frame: Vec<u8> = (0..12).collect();
+        let ser = open_ser(write_ser(&header, &[frame], &[])?)?;
+        assert_eq!(ser.read_frame_roi(0, 1, 1, 2, 2)?, vec![5, 6, 9, 10]);
+        assert_eq!(ser.read_frame_roi(0, 0, 0, 4, 3)?, ser.read_frame(0)?);
+        assert!(matches!(
+            ser.read_frame_roi(0, 3, 0, 2, 1),
+            Err(SerError::InvalidRegion { x: 3, .. })
+        ));
+        assert!(ser.read_frame_roi(0, 0, 2, 1, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn frame_statistics() -> Result<()> {
+        let header = mono8(2, 2, 1);
+        let ser = open_ser(write_ser(&header, &[vec![10, 40, 20, 30]], &[])?)?;
+        assert_eq!(
+            ser.frame_stats(0)?,
+            FrameStats {
+                min: 10,
+                max: 40,
+                mean: 25.0
+            }
+        );
+
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 1)
+            .pixel_depth(16)
+            .endianness(Endianness::BigEndian)
+            .frame_count(1)
+            .build();
+        let frame = encode_u16(&[1000, 3000], &Endianness::BigEndian);
+        let ser = open_ser(write_ser(&header, &[frame], &[])?)?;
+        assert_eq!(
+            ser.frame_stats(0)?,
+            FrameStats {
+                min: 1000,
+                max: 3000,
+                mean: 2000.0
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn histogram_of_flat_frame() -> Result<()> {
+        let header = mono8(4, 4, 1);
+        let ser = open_ser(write_ser(&header, &[vec![100; 16]], &[])?)?;
+        assert_eq!(ser.frame_histogram(0, 4)?, vec![0, 16, 0, 0]);
+        assert_eq!(ser.frame_histogram(0, 256)?[100], 16);
+        assert!(ser.frame_histogram(0, 0)?.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "debayer")]
+    #[test]
+    fn debayer_rggb() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(4, 4)
+            .bayer(Bayer::RGGB)
+            .frame_count(1)
+            .build();
+        // the top left 2x2 cell differs from the other three cells
+        let cell = |x: usize, y: usize| -> [u8; 4] {
+            if x < 2 && y < 2 {
+                [200, 100, 60, 20]
+            } else {
+                [10, 20, 40, 30]
+            }
+        };
+        let frame: Vec<u8> = (0..16)
+            .map(|i| cell(i % 4, i / 4)[(i / 4 % 2) * 2 + i % 2])
+            .collect();
+        let ser = open_ser(write_ser(&header, &[frame], &[])?)?;
+        let rgb = ser.read_frame_rgb(0)?;
+        assert_eq!(rgb.len(), 4 * 4 * 3);
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = if x < 2 && y < 2 {
+                    [200, 80, 20]
+                } else {
+                    [10, 30, 30]
+                };
+                let i = (y * 4 + x) * 3;
+                assert_eq!(rgb[i..i + 3], expected);
+            }
+        }
+
+        let header = mono8(4, 4, 1);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert!(matches!(
+            ser.read_frame_rgb(0),
+            Err(SerError::UnsupportedBayer(Bayer::Mono))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "debayer")]
+    #[test]
+    fn debayer_rejects_small_image() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(1, 4)
+            .bayer(Bayer::RGGB)
+            .frame_count(1)
+            .build();
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert!(matches!(
+            ser.read_frame_rgb(0),
+            Err(SerError::ImageTooSmall {
+                width: 1,
+                height: 4
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn split_into_parts() -> Result<()> {
+        let header = mono8(2, 2, 10);
+        let timestamps: Vec<u64> = (0..10).collect();
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &timestamps)?)?;
+        let basename = temp_path("split");
+        let filenames = ser.split(&basename, 4)?;
+        assert_eq!(filenames.len(), 3);
+        assert_eq!(filenames[0], format!("{}_part01.ser", basename));
+        let mut start = 0;
+        for (filename, count) in filenames.iter().zip([4, 4, 2].iter()) {
+            let part = SerFile::open(filename)?;
+            assert_eq!(part.header.frame_count, *count);
+            assert_eq!(part.read_frame(0)?, &[start as u8; 4]);
+            assert_eq!(part.timestamps, timestamps[start..start + count]);
+            start += count;
+            fs::remove_file(filename)?;
+        }
+        assert!(matches!(
+            ser.split(&basename, 0),
+            Err(SerError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn concat_files() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let a = open_ser(write_ser(&header, &numbered_frames(&header), &[1, 2, 3])?)?;
+        let frames: Vec<Vec<u8>> = (3..6).map(|i| vec![i; 4]).collect();
+        let b = open_ser(write_ser(&header, &frames, &[4, 5, 6])?)?;
+        let mut out = vec![];
+        concat(&[a, b], &mut out)?;
+        let ser = open_ser(out)?;
+        assert_eq!(ser.header.frame_count, 6);
+        for i in 0..6 {
+            assert_eq!(ser.read_frame(i)?, &[i as u8; 4]);
+        }
+        assert_eq!(ser.timestamps, vec![1, 2, 3, 4, 5, 6]);
+
+        let a = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let other = mono8(4, 1, 3);
+        let c = open_ser(write_ser(&other, &numbered_frames(&other), &[])?)?;
+        assert!(matches!(
+            concat(&[a, c], &mut vec![]),
+            Err(SerError::IncompatibleFile(1))
+        ));
+        assert!(matches!(
+            concat(&[], &mut vec![]),
+            Err(SerError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn read_frame_u16_zerocopy() -> Result<()> {
+        let (native, foreign) = if cfg!(target_endian = "little") {
+            (Endianness::LittleEndian, Endianness::BigEndian)
+        } else {
+            (Endianness::BigEndian, Endianness::LittleEndian)
+        };
+        let pixels = [0x1234, 0xabcd, 7, 65535];
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 2)
+            .pixel_depth(16)
+            .endianness(native.clone())
+            .frame_count(1)
+            .build();
+        let path = temp_path("zerocopy.ser");
+        fs::write(
+            &path,
+            write_ser(&header, &[encode_u16(&pixels, &native)], &[])?,
+        )?;
+        let ser = SerFile::open(&path)?;
+        assert_eq!(ser.read_frame_u16_zerocopy(0)?, &pixels);
+        fs::remove_file(&path)?;
+
+        let header = SerHeader {
+            endianness: foreign.clone(),
+            ..header
+        };
+        let ser = open_ser(write_ser(&header, &[encode_u16(&pixels, &foreign)], &[])?)?;
+        assert!(matches!(
+            ser.read_frame_u16_zerocopy(0),
+            Err(SerError::ZeroCopyUnavailable)
+        ));
+        assert_eq!(ser.read_frame_native(0)?, pixels);
+
+        let header = mono8(2, 2, 1);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert!(matches!(
+            ser.read_frame_u16_zerocopy(0),
+            Err(SerError::UnsupportedPixelDepth(8))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_frame_index_error() -> Result<()> {
+        let header = mono8(1, 1, 40);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let err = ser.read_frame(57).unwrap_err();
+        assert!(matches!(
+            err,
+            SerError::InvalidFrameIndex {
+                index: 57,
+                frame_count: 40
+            }
+        ));
+        assert_eq!(err.to_string(), "frame index 57 out of range 0..40");
+        Ok(())
+    }
+
+    #[test]
+    fn detect_ser_file() -> Result<()> {
+        assert_eq!(MAGIC.len(), 14);
+        let bytes = encode_header(&mono8(1, 1, 0), 0)?;
+        assert_eq!(bytes.len(), HEADER_SIZE);
+        assert!(is_ser_file(&bytes));
+        assert!(is_ser_file(&bytes[0..14]));
+        assert!(!is_ser_file(&bytes[0..10]));
+        assert!(!is_ser_file(b"NOT-A-SER-FILE"));
+        Ok(())
+    }
+
+    #[test]
+    fn image_data_reader() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let ser = open_ser(write_ser(&header, &frames, &[1, 2, 3])?)?;
+        let mut out = vec![];
+        io::copy(&mut ser.image_data_reader()?, &mut out)?;
+        assert_eq!(out, frames.concat());
+        Ok(())
+    }
+
+    #[test]
+    fn non_utf8_header_text() -> Result<()> {
+        let mut bytes = write_ser(&mono8(1, 1, 0), &[], &[])?;
+        bytes[42..49].copy_from_slice(b"Sky\xffCam");
+        let ser = open_ser(bytes)?;
+        assert_eq!(ser.header.observer, "Sky\u{fffd}Cam");
+        assert_eq!(&ser.observer_bytes()[..7], b"Sky\xffCam");
+        assert_eq!(ser.observer_bytes().len(), 40);
+        Ok(())
+    }
+
+    #[test]
+    fn display_bayer_and_endianness() {
+        assert_eq!(Bayer::RGGB.to_string(), "RGGB");
+        assert_eq!(Bayer::Mono.to_string(), "Mono");
+        assert_eq!(Bayer::Unknown(255).to_string(), "Unknown(0xff)");
+        assert_eq!(Endianness::LittleEndian.to_string(), "Little-endian");
+        assert_eq!(Endianness::BigEndian.to_string(), "Big-endian");
+    }
+
+    #[test]
+    fn parse_bayer() {
+        assert_eq!("RGGB".parse::<Bayer>(), Ok(Bayer::RGGB));
+        assert_eq!("rggb".parse::<Bayer>(), Ok(Bayer::RGGB));
+        assert_eq!("Bgr".parse::<Bayer>(), Ok(Bayer::BGR));
+        assert_eq!("mono".parse::<Bayer>(), Ok(Bayer::Mono));
+        let err = "XYZ".parse::<Bayer>().unwrap_err();
+        assert_eq!(err.to_string(), "unrecognized Bayer encoding 'XYZ'");
+    }
+
+    #[test]
+    fn empty_timestamps_write_no_trailer() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let bytes = write_ser(&header, &numbered_frames(&header), &[])?;
+        assert_eq!(bytes.len(), HEADER_SIZE + header.image_data_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn from_bytes() -> Result<()> {
+        let header = mono8(3, 3, 2);
+        let frames = numbered_frames(&header);
+        let ser = SerFile::from_bytes(write_ser(&header, &frames, &[5, 6])?)?;
+        assert_eq!(ser.header, header);
+        assert_eq!(ser.read_frame(1)?, frames[1].as_slice());
+        assert_eq!(ser.timestamps, vec![5, 6]);
+        assert!(matches!(
+            SerFile::from_bytes(vec![0; 10]),
+            Err(SerError::FileTooShort)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn open_with_or_without_memory_map() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let path = temp_path("no-mmap.ser");
+        fs::write(&path, write_ser(&header, &frames, &[1, 2, 3])?)?;
+        // the file is memory-mapped only when the mmap feature is enabled
+        let ser = SerFile::open(&path)?;
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(ser.read_frame(i)?, frame.as_slice());
+        }
+        assert_eq!(ser.timestamps, vec![1, 2, 3]);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn read_frame_range() -> Result<()> {
+        let header = mono8(2, 2, 4);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let expected = [ser.read_frame(1)?, ser.read_frame(2)?].concat();
+        assert_eq!(ser.read_frames(1, 3)?, expected.as_slice());
+        assert!(ser.read_frames(2, 2)?.is_empty());
+        assert!(matches!(
+            ser.read_frames(3, 5),
+            Err(SerError::InvalidFrameIndex { index: 5, .. })
+        ));
+        assert!(ser.read_frames(3, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn average_frames() -> Result<()> {
+        let header = mono8(2, 2, 2);
+        let frames = vec![vec![0, 10, 20, 30], vec![10, 20, 30, 41]];
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        assert_eq!(ser.average_frames(&[0, 1])?, vec![5, 15, 25, 35]);
+        assert!(matches!(
+            ser.average_frames(&[0, 2]),
+            Err(SerError::InvalidFrameIndex { index: 2, .. })
+        ));
+        assert!(matches!(
+            ser.average_frames(&[]),
+            Err(SerError::InvalidArgument(_))
+        ));
+
+        let header = SerHeaderBuilder::new()
+            .image_size(1, 1)
+            .pixel_depth(16)
+            .frame_count(2)
+            .build();
+        let frame = encode_u16(&[65535], &Endianness::LittleEndian);
+        let ser = open_ser(write_ser(&header, &[frame.clone(), frame], &[])?)?;
+        assert_eq!(ser.average_frames(&[0, 1])?, vec![65535]);
+        Ok(())
+    }
+
+    #[test]
+    fn write_frame_u16_round_trip() -> Result<()> {
+        let pixels = [1, 0x1234, 0xff00, 65535];
+        for endianness in [Endianness::LittleEndian, Endianness::BigEndian].iter() {
+            let header = SerHeaderBuilder::new()
+                .image_size(2, 2)
+                .pixel_depth(16)
+                .endianness(endianness.clone())
+                .frame_count(1)
+                .build();
+            let mut bytes = vec![];
+            let mut w = SerWriter::new(&mut bytes, &header)?;
+            w.write_frame_u16(&pixels)?;
+            assert!(w.write_frame_u16(&pixels[..3]).is_err());
+            w.finish()?;
+            let ser = open_ser(bytes)?;
+            assert_eq!(ser.read_frame_u16(0)?, pixels);
+        }
+
+        let header = mono8(2, 2, 1);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        assert!(matches!(
+            w.write_frame_u16(&pixels),
+            Err(SerError::UnsupportedPixelDepth(8))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn zero_image_size() -> Result<()> {
+        for (width, height) in [(0, 2), (2, 0)].iter() {
+            let header = mono8(*width, *height, 0);
+            let bytes = encode_header(&header, 0)?;
+            assert!(matches!(
+                open_ser(bytes),
+                Err(SerError::InvalidImageSize { width: w, height: h })
+                    if w == *width && h == *height
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn frames_on_disk() -> Result<()> {
+        let path = temp_path("frames-on-disk.ser");
+        fs::write(&path, truncated_ser(10, 6)?)?;
+        let ser = SerFile::open_lenient(&path)?;
+        assert_eq!(ser.declared_frame_count, 10);
+        assert_eq!(ser.frames_on_disk(), 6);
+        fs::remove_file(&path)?;
+
+        let header = mono8(2, 2, 3);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert_eq!(ser.frames_on_disk(), 3);
+        Ok(())
+    }
+
+    #[cfg(feature = "crc")]
+    #[test]
+    fn crc_detects_corrupted_image_data() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        for frame in &frames {
+            w.write_frame(frame)?;
+        }
+        w.write_timestamps(&[10, 20, 30])?;
+        w.write_crc()?;
+        w.finish()?;
+
+        let ser = open_ser(bytes.clone())?;
+        assert_eq!(ser.check_crc(), Some(true));
+        assert_eq!(ser.timestamps, vec![10, 20, 30]);
+        assert_eq!(ser.layout()?.trailer_bytes, 24);
+        assert_eq!(ser.layout()?.checksum_bytes, 4);
+        ser.verify()?;
+
+        bytes[HEADER_SIZE + 5] ^= 0xff;
+        let ser = open_ser(bytes)?;
+        assert_eq!(ser.check_crc(), Some(false));
+        assert!(matches!(ser.verify(), Err(SerError::ChecksumMismatch)));
+
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        assert_eq!(ser.check_crc(), None);
+        Ok(())
+    }
+
+    #[cfg(feature = "crc")]
+    #[test]
+    fn crc_without_timestamps() -> Result<()> {
+        let header = mono8(2, 2, 2);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        for frame in numbered_frames(&header) {
+            w.write_frame(&frame)?;
+        }
+        w.write_crc()?;
+        w.finish()?;
+        let ser = open_ser(bytes)?;
+        assert_eq!(ser.check_crc(), Some(true));
+        assert!(ser.timestamps.is_empty());
+        assert_eq!(ser.layout()?.trailer_bytes, 0);
+        ser.verify()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "crc")]
+    #[test]
+    fn append_updates_crc() -> Result<()> {
+        let header = mono8(2, 2, 2);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        for frame in numbered_frames(&header) {
+            w.write_frame(&frame)?;
+        }
+        w.write_timestamps(&[10, 20])?;
+        w.write_crc()?;
+        w.finish()?;
+        let path = temp_path("append-crc.ser");
+        fs::write(&path, &bytes)?;
+        let mut appender = SerAppender::open(&path)?;
+        appender.append_frame(&[2; 4], Some(30))?;
+        appender.finish()?;
+
+        let ser = SerFile::open(&path)?;
+        assert_eq!(ser.header.frame_count, 3);
+        assert_eq!(ser.timestamps, vec![10, 20, 30]);
+        assert_eq!(ser.check_crc(), Some(true));
+        ser.verify()?;
+
+        bytes[HEADER_SIZE] ^= 0xff;
+        fs::write(&path, &bytes)?;
+        assert!(matches!(
+            SerAppender::open(&path),
+            Err(SerError::ChecksumMismatch)
+        ));
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn open_async() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let path = temp_path("async.ser");
+        fs::write(&path, write_ser(&header, &frames, &[1, 2, 3])?)?;
+        let ser = SerFile::open_async(&path).await?;
+        assert_eq!(ser.header, header);
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(ser.read_frame(i)?, frame.as_slice());
+        }
+        assert_eq!(ser.timestamps, vec![1, 2, 3]);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn timestamped_frames() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let ser = open_ser(write_ser(&header, &frames, &[10, 20, 30])?)?;
+        let pairs = ser.timestamped_frames().collect::<Result<Vec<_>>>()?;
+        assert_eq!(pairs.len(), 3);
+        for (i, (ts, frame)) in pairs.iter().enumerate() {
+            assert_eq!(*ts, Some(10 * (i as u64 + 1)));
+            assert_eq!(*frame, frames[i].as_slice());
+        }
+
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        for pair in ser.timestamped_frames() {
+            assert_eq!(pair?.0, None);
+        }
+        assert_eq!(ser.timestamped_frames().count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn bin_frame() -> Result<()> {
+        let header = mono8(4, 4, 1);
+        let frame: Vec<u8> = (0..16).collect();
+        let ser = open_ser(write_ser(&header, &[frame], &[])?)?;
+        // the 2x2 blocks sum to 10, 18, 42, and 50
+        assert_eq!(ser.read_frame_binned(0, 2)?, (2, 2, vec![2, 4, 10, 12]));
+        assert_eq!(ser.read_frame_binned(0, 1)?.2, ser.read_frame_u16(0)?);
+        assert!(matches!(
+            ser.read_frame_binned(0, 3),
+            Err(SerError::InvalidBinningFactor {
+                width: 4,
+                height: 4,
+                factor: 3
+            })
+        ));
+        assert!(matches!(
+            ser.read_frame_binned(0, 0),
+            Err(SerError::InvalidBinningFactor { factor: 0, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn update_header_in_place() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 2)
+            .frame_count(3)
+            .observer("Before")
+            .build();
+        let frames = numbered_frames(&header);
+        let path = temp_path("update-header.ser");
+        fs::write(&path, write_ser(&header, &frames, &[1, 2, 3])?)?;
+
+        let updated = SerHeader {
+            observer: "After".to_string(),
+            ..header.clone()
+        };
+        update_header(&path, &updated)?;
+        let ser = SerFile::open(&path)?;
+        assert_eq!(ser.header, updated);
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(ser.read_frame(i)?, frame.as_slice());
+        }
+        assert_eq!(ser.timestamps, vec![1, 2, 3]);
+
+        let resized = SerHeader {
+            image_width: 4,
+            ..header
+        };
+        assert!(matches!(
+            update_header(&path, &resized),
+            Err(SerError::LayoutMismatch)
+        ));
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn frame_difference() -> Result<()> {
+        let header = mono8(2, 1, 2);
+        let ser = open_ser(write_ser(&header, &[vec![10, 20], vec![15, 5]], &[])?)?;
+        assert_eq!(ser.frame_diff(0, 1)?, vec![-5, 15]);
+        assert_eq!(ser.frame_diff(1, 0)?, vec![5, -15]);
+        assert!(matches!(
+            ser.frame_diff(0, 2),
+            Err(SerError::InvalidFrameIndex { index: 2, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn sharpness_of_high_contrast_frame() -> Result<()> {
+        let header = mono8(4, 4, 2);
+        let checkerboard: Vec<u8> = (0..16)
+            .map(|i| if (i % 4 + i / 4) % 2 == 0 { 0 } else { 255 })
+            .collect();
+        let ser = open_ser(write_ser(&header, &[checkerboard, vec![128; 16]], &[])?)?;
+        let sharp = ser.frame_sharpness(0)?;
+        let flat = ser.frame_sharpness(1)?;
+        assert_eq!(flat, 0.0);
+        assert!(sharp > flat);
+        Ok(())
+    }
+
+    #[test]
+    fn lu_id_round_trip() -> Result<()> {
+        let header = SerHeaderBuilder::new().image_size(1, 1).lu_id(42).build();
+        let ser = open_ser(write_ser(&header, &[], &[])?)?;
+        assert_eq!(ser.header.lu_id, 42);
+        Ok(())
+    }
+
+    #[test]
+    fn partial_trailer() -> Result<()> {
+        let header = mono8(2, 2, 5);
+        let mut bytes = write_ser(&header, &numbered_frames(&header), &[10, 20, 30, 40, 50])?;
+        bytes.truncate(bytes.len() - 16);
+        let ser = open_ser(bytes)?;
+        assert_eq!(ser.timestamps, vec![10, 20, 30]);
+        assert!(!ser.has_timestamps());
+        assert_eq!(ser.frame_timestamp(2), Some(30));
+        assert_eq!(ser.frame_timestamp(3), None);
+        Ok(())
+    }
+
+    #[test]
+    fn layout_with_trailer() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[1, 2, 3])?)?;
+        assert_eq!(
+            ser.layout()?,
+            Layout {
+                header_size: HEADER_SIZE,
+                image_data_bytes: 12,
+                trailer_bytes: 24,
+                checksum_bytes: 0,
+                total_bytes: HEADER_SIZE + 36,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn layout_of_truncated_file() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let mut ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        ser.header.frame_count = 5;
+        assert!(matches!(
+            ser.layout(),
+            Err(SerError::TruncatedImageData {
+                expected,
+                actual,
+            }) if expected == HEADER_SIZE + 20 && actual == HEADER_SIZE + 12
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn permissive_magic() -> Result<()> {
+        let mut bytes = write_ser(&mono8(1, 1, 0), &[], &[])?;
+        bytes[9..14].copy_from_slice(b"     ");
+        let path = temp_path("permissive-magic.ser");
+        fs::write(&path, &bytes)?;
+        assert!(matches!(SerFile::open(&path), Err(SerError::BadMagic)));
+        let ser = SerOpenOptions::new().permissive_magic(true).open(&path)?;
+        assert_eq!(ser.header.image_width, 1);
+
+        bytes[0..14].copy_from_slice(b"NOT-A-SER-FILE");
+        fs::write(&path, &bytes)?;
+        assert!(matches!(
+            SerOpenOptions::new().permissive_magic(true).open(&path),
+            Err(SerError::BadMagic)
+        ));
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn open_options() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let path = temp_path("open-options.ser");
+        fs::write(
+            &path,
+            write_ser(&header, &numbered_frames(&header), &[1, 2, 3])?,
+        )?;
+        let ser = SerOpenOptions::new().read_trailer(false).open(&path)?;
+        assert!(ser.timestamps.is_empty());
+        assert_eq!(ser.header.frame_count, 3);
+
+        fs::write(&path, truncated_ser(10, 6)?)?;
+        assert!(SerOpenOptions::new().open(&path).is_err());
+        let ser = SerOpenOptions::new()
+            .allow_truncated(true)
+            .memory_map(false)
+            .open(&path)?;
+        assert_eq!(ser.header.frame_count, 6);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn write_multiple_frames() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        assert_eq!(w.write_frames(frames.iter().map(|f| f.as_slice()))?, 3);
+        w.finish()?;
+        assert_eq!(bytes, write_ser(&header, &frames, &[])?);
+
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        assert!(w.write_frames(vec![&[0_u8; 4][..], &[0; 3]]).is_err());
+        assert_eq!(w.frames_written(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn frame_size_error_includes_index() -> Result<()> {
+        let header = mono8(2, 2, 2);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        w.write_frame(&[0; 4])?;
+        let err = w.write_frame(&[0; 3]).unwrap_err();
+        assert_eq!(err.to_string(), "frame 1: expected 4 bytes, got 3");
+        Ok(())
+    }
+
+    #[test]
+    fn decimate_frames() -> Result<()> {
+        let header = mono8(2, 2, 10);
+        let timestamps: Vec<u64> = (0..10).map(|i| 100 * i).collect();
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &timestamps)?)?;
+        let mut out = vec![];
+        assert_eq!(ser.decimate(0, 3, &mut out)?, 4);
+        let decimated = open_ser(out)?;
+        assert_eq!(decimated.header.frame_count, 4);
+        for (i, frame) in [0, 3, 6, 9].iter().enumerate() {
+            assert_eq!(decimated.read_frame(i)?, &[*frame as u8; 4]);
+        }
+        assert_eq!(decimated.timestamps, vec![0, 300, 600, 900]);
+
+        let mut out = vec![];
+        assert_eq!(ser.decimate(1, 4, &mut out)?, 3);
+        assert_eq!(open_ser(out)?.timestamps, vec![100, 500, 900]);
+        assert!(ser.decimate(0, 0, &mut vec![]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn finish_flushes_buffered_writer() -> Result<()> {
+        let header = mono8(2, 2, 2);
+        let frames = numbered_frames(&header);
+        let mut out = vec![];
+        let mut buf = io::BufWriter::new(&mut out);
+        let mut w = SerWriter::new(&mut buf, &header)?;
+        for frame in &frames {
+            w.write_frame(frame)?;
+        }
+        w.write_timestamps(&[1, 2])?;
+        w.finish()?;
+        assert_eq!(buf.get_ref().len(), HEADER_SIZE + 8 + 16);
+        drop(buf);
+        assert_eq!(out, write_ser(&header, &frames, &[1, 2])?);
+        Ok(())
+    }
+
+    #[test]
+    fn max_pixel_value() {
+        let header = |depth| SerHeaderBuilder::new().pixel_depth(depth).build();
+        assert_eq!(header(8).max_pixel_value(), 255);
+        assert_eq!(header(12).max_pixel_value(), 4095);
+        assert_eq!(header(14).max_pixel_value(), 16383);
+        assert_eq!(header(16).max_pixel_value(), 65535);
+    }
+
+    #[test]
+    fn detect_bit_alignment() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 1)
+            .pixel_depth(12)
+            .frame_count(2)
+            .build();
+        let file = |pixels: [[u16; 2]; 2]| -> Result<SerFile> {
+            let frames: Vec<Vec<u8>> = pixels
+                .iter()
+                .map(|p| encode_u16(p, &Endianness::LittleEndian))
+                .collect();
+            open_ser(write_ser(&header, &frames, &[])?)
+        };
+        let left = file([[0x0010, 0xfff0], [0x8000, 0x1230]])?;
+        assert_eq!(left.detect_bit_alignment(2), BitAlignment::LeftAligned);
+        let right = file([[0x0001, 0x0fff], [0x0800, 0x0123]])?;
+        assert_eq!(right.detect_bit_alignment(2), BitAlignment::RightAligned);
+        let dark = file([[0; 2], [0; 2]])?;
+        assert_eq!(dark.detect_bit_alignment(2), BitAlignment::Unknown);
+
+        let header = mono8(2, 1, 1);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert_eq!(ser.detect_bit_alignment(1), BitAlignment::Unknown);
+        Ok(())
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn mono_frame_to_image() -> Result<()> {
+        let header = mono8(3, 2, 1);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let image = ser.frame_to_image(0)?.into_luma8();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.get_pixel(2, 1).0, [ser.read_frame(0)?[5]]);
+
+        let header = SerHeaderBuilder::new()
+            .image_size(3, 2)
+            .pixel_depth(12)
+            .endianness(Endianness::BigEndian)
+            .frame_count(1)
+            .build();
+        let frame = encode_u16(&[0, 1, 2, 3, 4, 4095], &Endianness::BigEndian);
+        let ser = open_ser(write_ser(&header, &[frame], &[])?)?;
+        let image = ser.frame_to_image(0)?;
+        assert!(matches!(image, image::DynamicImage::ImageLuma16(_)));
+        let image = image.into_luma16();
+        assert_eq!(image.dimensions(), (3, 2));
+        assert_eq!(image.get_pixel(1, 0).0, [16]);
+        assert_eq!(image.get_pixel(2, 1).0, [65520]);
+        Ok(())
+    }
+
+    #[test]
+    fn owned_frame_chunks() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let borrowed: Vec<Vec<u8>> = ser
+            .frames()
+            .map(|f| f.map(|f| f.to_vec()))
+            .collect::<Result<_>>()?;
+        let chunks =
+            std::thread::spawn(move || ser.into_frame_chunks().collect::<Result<Vec<_>>>())
+                .join()
+                .unwrap()?;
+        assert_eq!(chunks, borrowed);
+        Ok(())
+    }
+
+    #[test]
+    fn read_header_only() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 2)
+            .frame_count(2)
+            .telescope("Telescope")
+            .build();
+        let path = temp_path("read-header.ser");
+        fs::write(&path, write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert_eq!(SerHeader::read_from(&path)?, SerFile::open(&path)?.header);
+        fs::write(&path, [0; 10])?;
+        assert!(matches!(
+            SerHeader::read_from(&path),
+            Err(SerError::FileTooShort)
+        ));
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_trailer() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let mut bytes = write_ser(&header, &numbered_frames(&header), &[])?;
+        for ts in [1, 2, 10, 20, 30].iter() {
+            bytes.write_u64::<LittleEndian>(*ts)?;
+        }
+        let ser = open_ser(bytes)?;
+        assert_eq!(ser.timestamps, vec![10, 20, 30]);
+        assert_eq!(ser.trailer_extra_bytes(), 16);
+        ser.verify()?;
+        Ok(())
+    }
+
+    #[test]
+    fn timestamps_at_constant_frame_rate() -> Result<()> {
+        let header = mono8(1, 1, 10);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        for frame in numbered_frames(&header) {
+            w.write_frame(&frame)?;
+        }
+        w.write_timestamps_at_fps(TICKS_2021, 30.0)?;
+        w.finish()?;
+        let ser = open_ser(bytes)?;
+        assert_eq!(ser.timestamps.len(), 10);
+        assert_eq!(ser.timestamps[0], TICKS_2021);
+        assert_eq!(ser.timestamps[9] - ser.timestamps[0], 3_000_000);
+        for pair in ser.timestamps.windows(2) {
+            assert!((333_333..=333_334).contains(&(pair[1] - pair[0])));
+        }
+
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        assert!(w.write_timestamps_at_fps(TICKS_2021, 0.0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn timestamps_at_frame_rate_overflow() -> Result<()> {
+        let header = mono8(1, 1, 2);
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        for frame in numbered_frames(&header) {
+            w.write_frame(&frame)?;
+        }
+        let err = w.write_timestamps_at_fps(u64::MAX, 30.0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(w.write_timestamps_at_fps(0, 1e-300).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn timestamps_look_valid() -> Result<()> {
+        let header = mono8(1, 1, 2);
+        let frames = numbered_frames(&header);
+        let ser = open_ser(write_ser(&header, &frames, &[TICKS_2021, TICKS_2021 + 1])?)?;
+        assert!(ser.timestamps_look_valid());
+        let ser = open_ser(write_ser(&header, &frames, &[1, 2])?)?;
+        assert!(!ser.timestamps_look_valid());
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        assert!(!ser.timestamps_look_valid());
+        Ok(())
+    }
+
+    #[test]
+    fn repair_truncated_frame_count() -> Result<()> {
+        let path = temp_path("repair.ser");
+        fs::write(&path, truncated_ser(10, 6)?)?;
+        assert!(SerFile::open(&path).is_err());
+        assert_eq!(repair_frame_count(&path)?, 6);
+
+        let ser = SerFile::open(&path)?;
+        assert_eq!(ser.header.frame_count, 6);
+        assert_eq!(ser.declared_frame_count, 6);
+        assert!(ser.timestamps.is_empty());
+        assert_eq!(ser.layout()?.total_bytes, HEADER_SIZE + 24);
+        assert_eq!(ser.read_frame(5)?, &[5; 4]);
+        ser.verify()?;
+
+        assert_eq!(repair_frame_count(&path)?, 6);
+        assert_eq!(fs::metadata(&path)?.len() as usize, HEADER_SIZE + 24);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn split_color_planes() -> Result<()> {
+        let frame = vec![1, 2, 3, 4, 5, 6];
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 1)
+            .bayer(Bayer::RGB)
+            .frame_count(1)
+            .build();
+        let ser = open_ser(write_ser(&header, std::slice::from_ref(&frame), &[])?)?;
+        assert_eq!(
+            ser.read_frame_planes(0)?,
+            [vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+
+        let header = SerHeader {
+            bayer: Bayer::BGR,
+            ..header
+        };
+        let ser = open_ser(write_ser(&header, &[frame], &[])?)?;
+        assert_eq!(
+            ser.read_frame_planes(0)?,
+            [vec![3, 6], vec![2, 5], vec![1, 4]]
+        );
+
+        let header = SerHeaderBuilder::new()
+            .image_size(1, 1)
+            .pixel_depth(16)
+            .bayer(Bayer::RGB)
+            .frame_count(1)
+            .build();
+        let ser = open_ser(write_ser(&header, &[vec![1, 2, 3, 4, 5, 6]], &[])?)?;
+        assert_eq!(
+            ser.read_frame_planes(0)?,
+            [vec![1, 2], vec![3, 4], vec![5, 6]]
+        );
+
+        let header = mono8(2, 1, 1);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert!(matches!(
+            ser.read_frame_planes(0),
+            Err(SerError::UnsupportedBayer(Bayer::Mono))
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_writer() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let path = temp_path("mmap-writer.ser");
+        let mut w = SerMmapWriter::create(&path, &header)?;
+        for frame in &frames {
+            w.write_frame(frame)?;
+        }
+        assert!(matches!(
+            w.write_frame(&[0; 4]),
+            Err(SerError::InvalidFrameIndex { index: 3, .. })
+        ));
+        w.write_timestamps(&[10, 20, 30])?;
+        assert_eq!(w.frames_written(), 3);
+        w.finish()?;
+        assert_eq!(
+            fs::read(&path)?,
+            write_ser(&header, &frames, &[10, 20, 30])?
+        );
+        let ser = SerFile::open(&path)?;
+        assert_eq!(ser.read_frame(2)?, frames[2].as_slice());
+        assert_eq!(ser.timestamps, vec![10, 20, 30]);
+
+        let mut w = SerMmapWriter::create(&path, &header)?;
+        for frame in &frames {
+            w.write_frame(frame)?;
+        }
+        assert!(w.write_timestamps(&[10, 20]).is_err());
+        w.finish()?;
+        assert_eq!(fs::read(&path)?, write_ser(&header, &frames, &[])?);
+
+        let mut w = SerMmapWriter::create(&path, &header)?;
+        w.write_frame(&frames[0])?;
+        assert!(matches!(
+            w.finish(),
+            Err(SerError::FrameCountMismatch {
+                expected: 3,
+                written: 1
+            })
+        ));
+        assert_eq!(fs::metadata(&path)?.len() as usize, HEADER_SIZE + 12 + 24);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn frame_view_coordinates() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(3, 2)
+            .pixel_depth(16)
+            .endianness(Endianness::BigEndian)
+            .frame_count(1)
+            .build();
+        let frame = encode_u16(&[0, 100, 200, 300, 400, 500], &Endianness::BigEndian);
+        let ser = open_ser(write_ser(&header, &[frame], &[])?)?;
+        let view = ser.frame_view(0)?;
+        assert_eq!((view.width(), view.height()), (3, 2));
+        assert_eq!(view.get(0, 0), 0);
+        assert_eq!(view.get(2, 0), 200);
+        assert_eq!(view.get(0, 1), 300);
+        assert_eq!(view.get(2, 1), 500);
+
+        let header = SerHeaderBuilder::new()
+            .image_size(1, 1)
+            .bayer(Bayer::RGB)
+            .frame_count(1)
+            .build();
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        assert!(matches!(
+            ser.frame_view(0),
+            Err(SerError::UnsupportedBayer(Bayer::RGB))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn pathological_header_overflows_cleanly() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(u32::MAX, u32::MAX)
+            .pixel_depth(16)
+            .build();
+        assert!(matches!(
+            open_ser(encode_header(&header, 1)?),
+            Err(SerError::SizeOverflow)
+        ));
+        let header = SerHeaderBuilder::new()
+            .image_size(65536, 65536)
+            .pixel_depth(16)
+            .build();
+        assert!(matches!(
+            open_ser(encode_header(&header, u32::MAX as usize)?),
+            Err(SerError::SizeOverflow)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn copy_with_transform() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[1, 2, 3])?)?;
+        let mut out = vec![];
+        ser.copy_transform(&ser.header, &mut out, |frame| {
+            frame.iter().map(|p| 255 - p).collect()
+        })?;
+        let copy = open_ser(out)?;
+        for i in 0..3 {
+            assert_eq!(copy.read_frame(i)?, &[255 - i as u8; 4]);
+        }
+        assert_eq!(copy.timestamps, vec![1, 2, 3]);
+
+        let cropped = mono8(1, 1, 0);
+        let mut out = vec![];
+        ser.copy_transform(&cropped, &mut out, |frame| frame[..1].to_vec())?;
+        let copy = open_ser(out)?;
+        assert_eq!(copy.header.frame_count, 3);
+        assert_eq!(copy.read_frame(2)?, &[2]);
+        Ok(())
+    }
+
+    #[test]
+    fn frames_in_reverse() -> Result<()> {
+        let header = mono8(2, 2, 4);
+        let ser = open_ser(write_ser(
+            &header,
+            &numbered_frames(&header),
+            &[1, 2, 3, 4],
+        )?)?;
+        let reversed = ser.frames_rev().collect::<Result<Vec<_>>>()?;
+        let expected = (0..4)
+            .rev()
+            .map(|i| ser.read_frame(i))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(reversed, expected);
+        let timestamps = ser
+            .timestamped_frames()
+            .rev()
+            .map(|pair| pair.map(|(ts, _)| ts))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(timestamps, vec![Some(4), Some(3), Some(2), Some(1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn median_of_frames() -> Result<()> {
+        let header = mono8(3, 1, 3);
+        let frames = vec![vec![1, 5, 9], vec![3, 2, 7], vec![2, 8, 8]];
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        assert_eq!(ser.median_frame(&[0, 1, 2])?, vec![2, 5, 8]);
+        assert_eq!(ser.median_frame(&[0, 1])?, vec![2, 3, 8]);
+        assert_eq!(ser.median_frame(&[2])?, vec![2, 8, 8]);
+        assert!(matches!(
+            ser.median_frame(&[]),
+            Err(SerError::InvalidArgument(_))
+        ));
+        assert!(ser.median_frame(&[0, 3]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn mmapped_or_owned() -> Result<()> {
+        let header = mono8(2, 2, 1);
+        let bytes = write_ser(&header, &numbered_frames(&header), &[])?;
+        let path = temp_path("is-mmapped.ser");
+        fs::write(&path, &bytes)?;
+        assert_eq!(SerFile::open(&path)?.is_mmapped(), cfg!(feature = "mmap"));
+        assert!(!open_ser(bytes)?.is_mmapped());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn typed_timestamp_conversions() -> Result<()> {
+        let ts = SerTimestamp::from(UNIX_EPOCH_TICKS + 15);
+        assert_eq!(ts.ticks(), UNIX_EPOCH_TICKS + 15);
+        assert_eq!(ts.as_unix_nanos(), 1500);
+        assert_eq!(SerTimestamp(UNIX_EPOCH_TICKS - 1).as_unix_nanos(), -100);
+        assert_eq!(
+            SerTimestamp(TICKS_2021).as_unix_nanos(),
+            1_609_459_200_000_000_000
+        );
+
+        let header = mono8(1, 1, 2);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[5, 6])?)?;
+        assert_eq!(
+            ser.typed_timestamps().collect::<Vec<_>>(),
+            vec![SerTimestamp(5), SerTimestamp(6)]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn typed_timestamp_to_datetime() {
+        use chrono::SecondsFormat;
+
+        let ts = SerTimestamp(637_185_764_967_890_000);
+        assert_eq!(
+            ts.to_datetime()
+                .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            Some("2020-02-29T12:34:56.789Z".to_string())
+        );
+        assert_eq!(
+            SerTimestamp(UNIX_EPOCH_TICKS).to_datetime(),
+            Utc.timestamp_opt(0, 0).single()
+        );
+        assert_eq!(SerTimestamp(0).to_datetime(), None);
+    }
+
+    #[test]
+    fn color_type_from_code() {
+        let color_type = |code| {
+            SerHeaderBuilder::new()
+                .bayer(Bayer::from_code(code))
+                .build()
+                .color_type()
+        };
+        assert_eq!(color_type(0), ColorType::Mono);
+        for code in [8, 9, 10, 11, 16, 17, 18, 19].iter() {
+            assert_eq!(color_type(*code), ColorType::Bayer);
+        }
+        assert_eq!(color_type(100), ColorType::Rgb);
+        assert_eq!(color_type(101), ColorType::Rgb);
+        assert_eq!(color_type(7), ColorType::Unknown);
+    }
+
+    #[test]
+    fn zero_frame_file() -> Result<()> {
+        let header = mono8(2, 2, 0);
+        let ser = open_ser(write_ser(&header, &[], &[])?)?;
+        assert_eq!(ser.header.frame_count, 0);
+        assert_eq!(ser.frames().count(), 0);
+        assert_eq!(ser.frames_rev().count(), 0);
+        assert_eq!(ser.timestamped_frames().count(), 0);
+        assert!(matches!(
+            ser.read_frame(0),
+            Err(SerError::InvalidFrameIndex {
+                index: 0,
+                frame_count: 0
+            })
+        ));
+        assert!(matches!(
+            ser.frame_stats(0),
+            Err(SerError::InvalidFrameIndex { .. })
+        ));
+        assert!(ser.frame_histogram(0, 4).is_err());
+        assert_eq!(ser.average_fps(), None);
+        assert_eq!(ser.duration(), None);
+        assert_eq!(ser.chronological_order(), None);
+        assert!(ser.detect_dropped_frames(2.0).is_empty());
+        assert_eq!(ser.frames_on_disk(), 0);
+        assert!(ser.read_frames(0, 0)?.is_empty());
+        ser.verify()?;
+        assert_eq!(ser.into_frame_chunks().count(), 0);
+
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 2)
+            .pixel_depth(12)
+            .build();
+        let ser = open_ser(write_ser(&header, &[], &[])?)?;
+        assert_eq!(ser.detect_bit_alignment(4), BitAlignment::Unknown);
+        Ok(())
+    }
+
+    #[test]
+    fn frame_alignment_matches_pointer() -> Result<()> {
+        let header = mono8(3, 1, 4);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        for i in 0..4 {
+            let address = ser.read_frame(i)?.as_ptr() as usize;
+            let alignment = ser.frame_alignment(i)?;
+            assert!(alignment.is_power_of_two());
+            assert_eq!(address % alignment, 0);
+            assert_ne!(address % (2 * alignment), 0);
+        }
+        assert!(ser.frame_alignment(4).is_err());
+
+        let path = temp_path("alignment.ser");
+        fs::write(&path, write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let ser = SerFile::open(&path)?;
+        if ser.is_mmapped() {
+            // the mapping is page-aligned, so the alignment comes from the 178-byte header
+            assert_eq!(ser.frame_alignment(0)?, 2);
+        }
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn explode_into_files() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        let dir = PathBuf::from(temp_path("explode"));
+        fs::create_dir_all(&dir)?;
+        let paths = ser.explode(&dir, "raw")?;
+        assert_eq!(paths.len(), 3);
+        for (i, path) in paths.iter().enumerate() {
+            assert_eq!(path, &dir.join(format!("frame_0000{}.raw", i)));
+            assert_eq!(fs::read(path)?, ser.read_frame(i)?);
+        }
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn checked_sizes() {
+        let header = SerHeaderBuilder::new()
+            .image_size(u32::MAX, u32::MAX)
+            .pixel_depth(16)
+            .frame_count(1)
+            .build();
+        assert!(matches!(
+            header.try_image_frame_size(),
+            Err(SerError::SizeOverflow)
+        ));
+        assert!(matches!(
+            header.try_image_data_bytes(),
+            Err(SerError::SizeOverflow)
+        ));
+        assert_eq!(header.image_frame_size(), usize::MAX);
+
+        let header = SerHeaderBuilder::new()
+            .image_size(640, 480)
+            .pixel_depth(16)
+            .frame_count(usize::MAX)
+            .build();
+        assert_eq!(header.try_image_frame_size().ok(), Some(640 * 480 * 2));
+        assert!(matches!(
+            header.try_image_data_bytes(),
+            Err(SerError::SizeOverflow)
+        ));
+        assert_eq!(header.image_data_bytes(), usize::MAX);
+    }
+
+    #[test]
+    fn chronological_order() -> Result<()> {
+        let header = mono8(1, 1, 4);
+        let frames = numbered_frames(&header);
+        let ser = open_ser(write_ser(&header, &frames, &[30, 10, 20, 10])?)?;
+        assert_eq!(ser.chronological_order(), Some(vec![1, 3, 2, 0]));
+        let ser = open_ser(write_ser(&header, &frames, &[])?)?;
+        assert_eq!(ser.chronological_order(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn writer_validates_header() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(1, 1)
+            .observer(&"x".repeat(50))
+            .build();
+        let ser = open_ser(write_ser(&header, &[], &[])?)?;
+        assert_eq!(ser.header.observer, "x".repeat(40));
+
+        #[cfg(target_pointer_width = "64")]
+        {
+            let frame_count = u32::MAX as usize + 1;
+            assert!(matches!(
+                encode_header(&header, frame_count),
+                Err(SerError::FrameCountOverflow(n)) if n == frame_count
+            ));
+            let header = SerHeader {
+                frame_count,
+                ..header
+            };
+            assert!(matches!(
+                SerWriter::new(&mut Vec::<u8>::new(), &header),
+                Err(SerError::FrameCountOverflow(n)) if n == frame_count
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn advise_sequential() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let frames = numbered_frames(&header);
+        let path = temp_path("advise.ser");
+        fs::write(&path, write_ser(&header, &frames, &[])?)?;
+        let ser = SerFile::open(&path)?;
+        ser.advise_sequential()?;
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(ser.read_frame(i)?, frame.as_slice());
+        }
+        open_ser(write_ser(&header, &frames, &[])?)?.advise_sequential()?;
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn read_frame_beyond_data() -> Result<()> {
+        let header = mono8(2, 2, 3);
+        let mut ser = open_ser(write_ser(&header, &numbered_frames(&header), &[])?)?;
+        // the header fields are public, so they can disagree with the validated data
+        ser.header.frame_count = 5;
+        assert!(ser.read_frame(2).is_ok());
+        assert!(matches!(
+            ser.read_frame(4),
+            Err(SerError::TruncatedImageData { .. })
+        ));
+        assert!(matches!(
+            ser.read_frames(0, 5),
+            Err(SerError::TruncatedImageData { .. })
+        ));
+        assert!(matches!(
+            ser.image_data_reader(),
+            Err(SerError::TruncatedImageData { .. })
+        ));
+        assert!(matches!(
+            ser.timestamped_frames().nth(3),
+            Some(Err(SerError::TruncatedImageData { .. }))
+        ));
+        assert!(matches!(
+            ser.layout(),
+            Err(SerError::TruncatedImageData { .. })
+        ));
+        assert_eq!(ser.checksum_bytes(), 0);
+
+        ser.header.image_width = 1000;
+        assert!(matches!(
+            ser.read_frame(1),
+            Err(SerError::TruncatedImageData { .. })
+        ));
+
+        ser.header.image_width = 2;
+        let chunks = ser.into_frame_chunks().collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks[..3].iter().all(|chunk| chunk.is_ok()));
+        assert!(matches!(
+            chunks[3],
+            Err(SerError::TruncatedImageData { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn timestamps_csv() -> Result<()> {
+        let header = mono8(1, 1, 2);
+        // 2021-01-01T00:00:00Z and 2020-02-29T12:34:56.789Z
+        let timestamps = [TICKS_2021, 637_185_764_967_890_000];
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &timestamps)?)?;
+        let mut out = vec![];
+        ser.write_timestamps_csv(&mut out)?;
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            lines,
+            vec![
+                "frame,ticks,utc_iso",
+                "0,637450560000000000,2021-01-01T00:00:00Z",
+                "1,637185764967890000,2020-02-29T12:34:56.789Z",
+            ]
+        );
+        #[cfg(not(feature = "chrono"))]
+        assert_eq!(
+            lines,
+            vec![
+                "frame,ticks",
+                "0,637450560000000000",
+                "1,637185764967890000",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn header_with_frame_count() {
+        let header = SerHeaderBuilder::new()
+            .image_size(3, 2)
+            .pixel_depth(10)
+            .bayer(Bayer::BGGR)
+            .observer("Observer")
+            .frame_count(5)
+            .build();
+        let copy = header.with_frame_count(7);
+        assert_eq!(copy.frame_count, 7);
+        assert_ne!(copy, header);
+        assert_eq!(copy.with_frame_count(5), header);
+    }
+
+    #[test]
+    fn compare_frames_across_endianness() -> Result<()> {
+        let pixels = [1, 0x1234, 0xff00, 65535];
+        let file = |endianness: Endianness, pixels: &[u16]| -> Result<SerFile> {
+            let header = SerHeaderBuilder::new()
+                .image_size(2, 2)
+                .pixel_depth(16)
+                .endianness(endianness.clone())
+                .frame_count(1)
+                .build();
+            open_ser(write_ser(&header, &[encode_u16(pixels, &endianness)], &[])?)
+        };
+        let little = file(Endianness::LittleEndian, &pixels)?;
+        let big = file(Endianness::BigEndian, &pixels)?;
+        assert_ne!(little.read_frame(0)?, big.read_frame(0)?);
+        assert!(little.frames_equal_logical(0, &big, 0)?);
+        let other = file(Endianness::BigEndian, &[1, 0x1234, 0xff00, 0])?;
+        assert!(!little.frames_equal_logical(0, &other, 0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn detect_dropped_frames() -> Result<()> {
+        let header = mono8(1, 1, 8);
+        let timestamps = [0, 100, 200, 300, 400, 500, 850, 950];
+        let ser = open_ser(write_ser(&header, &numbered_frames(&header), &timestamps)?)?;
+        assert_eq!(ser.detect_dropped_frames(1.5), vec![(6, 350)]);
+        assert!(ser.detect_dropped_frames(4.0).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn write_big_endian_pixels() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 1)
+            .pixel_depth(16)
+            .endianness(Endianness::BigEndian)
+            .frame_count(1)
+            .build();
+        let mut bytes = vec![];
+        let mut w = SerWriter::new(&mut bytes, &header)?;
+        w.write_frame_u16(&[0x1234, 0xabcd])?;
+        w.finish()?;
+        assert_eq!(parse_u32(&bytes[22..26])?, 1);
+        assert_eq!(bytes[HEADER_SIZE..], [0x12, 0x34, 0xab, 0xcd]);
+        let ser = open_ser(bytes)?;
+        assert_eq!(ser.header.endianness, Endianness::BigEndian);
+        assert_eq!(ser.read_frame_u16(0)?, vec![0x1234, 0xabcd]);
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_writer_u16() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(2, 1)
+            .pixel_depth(16)
+            .endianness(Endianness::BigEndian)
+            .frame_count(1)
+            .build();
+        let path = temp_path("mmap-writer-u16.ser");
+        let mut w = SerMmapWriter::create(&path, &header)?;
+        w.write_frame_u16(&[0x1234, 0xabcd])?;
+        w.finish()?;
+        assert_eq!(fs::read(&path)?[HEADER_SIZE..], [0x12, 0x34, 0xab, 0xcd]);
+
+        let mut w = SerMmapWriter::create(&path, &mono8(2, 1, 1))?;
+        assert!(matches!(
+            w.write_frame_u16(&[1, 2]),
+            Err(SerError::UnsupportedPixelDepth(8))
+        ));
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn parse_header_bytes() -> Result<()> {
+        let header = SerHeaderBuilder::new()
+            .image_size(640, 480)
+            .pixel_depth(12)
+            .bayer(Bayer::RGGB)
+            .instrument("Camera")
+            .build();
+        let mut bytes = encode_header(&header, 25)?;
+        assert_eq!(SerHeader::parse(&bytes)?, header.with_frame_count(25));
+        bytes.extend_from_slice(&[0; 16]);
+        assert_eq!(SerHeader::parse(&bytes)?.frame_count, 25);
+        assert!(matches!(
+            SerHeader::parse(&bytes[..HEADER_SIZE - 1]),
+            Err(SerError::FileTooShort)
+        ));
+        bytes[0] = b'X';
+        assert!(matches!(SerHeader::parse(&bytes), Err(SerError::BadMagic)));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_short_slices() {
+        assert!(parse_u32(&[1, 2, 3]).is_err());
+        assert!(parse_u64(&[1, 2, 3, 4, 5, 6, 7]).is_err());
+        assert_eq!(parse_u32(&[1, 0, 0, 0]).ok(), Some(1));
+        assert_eq!(parse_u64(&[0, 1, 0, 0, 0, 0, 0, 0]).ok(), Some(256));
+    }
 }